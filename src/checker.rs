@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+
+use crate::{
+    expr::{Expression, Literal},
+    pattern::Pattern,
+    statement::Statement,
+    token::{Token, TokenType},
+};
+
+// Static types for the `check` command. `Any` is the gradual part: a variable
+// that has held values of different types, or anything the checker cannot
+// work out, is never reported.
+#[derive(Clone, PartialEq)]
+pub(crate) enum Type {
+    Number,
+    String,
+    Bool,
+    Nil,
+    List,
+    Map,
+    Any,
+}
+
+impl Type {
+    pub(crate) fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "number" => Some(Type::Number),
+            "string" => Some(Type::String),
+            "bool" => Some(Type::Bool),
+            "nil" => Some(Type::Nil),
+            "list" => Some(Type::List),
+            "map" => Some(Type::Map),
+            "any" => Some(Type::Any),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn of(value: &Literal) -> Type {
+        match value {
+            Literal::Number(_) | Literal::Int(_) => Type::Number,
+            Literal::String(_) => Type::String,
+            Literal::Bool(_) => Type::Bool,
+            Literal::Nil => Type::Nil,
+            Literal::List(_) => Type::List,
+            Literal::Map(_) => Type::Map,
+        }
+    }
+
+    // Whether a value of type `other` can be stored where `self` is expected.
+    pub(crate) fn accepts(&self, other: &Type) -> bool {
+        return *self == Type::Any || *other == Type::Any || self == other;
+    }
+
+    fn is(&self, expected: Type) -> bool {
+        return *self == Type::Any || *self == expected;
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Bool => "bool",
+            Type::Nil => "nil",
+            Type::List => "list",
+            Type::Map => "map",
+            Type::Any => "any",
+        };
+        f.write_str(name)
+    }
+}
+
+// Arity and result type of the native list and map methods.
+fn method_signature(receiver: &Type, name: &str) -> Option<(usize, Type)> {
+    match (receiver, name) {
+        (Type::List, "push") => Some((1, Type::Nil)),
+        (Type::List, "pop") => Some((0, Type::Any)),
+        (Type::List, "len") => Some((0, Type::Number)),
+        (Type::List, "insert") => Some((2, Type::Nil)),
+        (Type::List, "remove") => Some((1, Type::Any)),
+        (Type::List, "slice") => Some((2, Type::List)),
+        (Type::Map, "keys") => Some((0, Type::List)),
+        (Type::Map, "values") => Some((0, Type::List)),
+        (Type::Map, "has") => Some((1, Type::Bool)),
+        (Type::Map, "remove") => Some((1, Type::Any)),
+        (Type::Map, "len") => Some((0, Type::Number)),
+        _ => None,
+    }
+}
+
+// What the checker knows about a variable. An annotated type is enforced on
+// every assignment. An inferred one comes from the initializer and widens to
+// `Any` once a value of another type is assigned.
+#[derive(Clone)]
+struct Binding {
+    declared: Type,
+    annotated: bool,
+}
+
+struct Checker {
+    // Variables in scope, innermost scope last.
+    scopes: Vec<HashMap<String, Binding>>,
+    errors: Vec<String>,
+}
+
+impl Checker {
+    fn error(&mut self, line: i32, message: String) {
+        self.errors.push(format!("[line {}] Type error: {}", line, message));
+    }
+
+    fn declare(&mut self, name: &str, declared: Type, annotated: bool) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_string(), Binding { declared, annotated });
+    }
+
+    fn binding(&mut self, name: &str) -> Option<&mut Binding> {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(binding) = scope.get_mut(name) {
+                return Some(binding);
+            }
+        }
+        return None;
+    }
+
+    fn lookup(&mut self, name: &str) -> Type {
+        match self.binding(name) {
+            Some(binding) => binding.declared.clone(),
+            // Unknown names may come from an `import`, so they are not reported here.
+            None => Type::Any,
+        }
+    }
+
+    fn check_block(&mut self, stats: &[Statement]) {
+        self.scopes.push(HashMap::new());
+        for stat in stats {
+            self.check_stat(stat);
+        }
+        self.scopes.pop();
+    }
+
+    fn check_stat(&mut self, stat: &Statement) {
+        match stat {
+            Statement::PrintStatement { expr, .. } => {
+                self.infer(expr);
+            }
+            Statement::ExprStatement { expr, .. } => {
+                self.infer(expr);
+            }
+            Statement::ThrowStatement { expr, .. } => {
+                self.infer(expr);
+            }
+            Statement::DeclStatement {
+                name,
+                value,
+                constant,
+                annotation,
+                ..
+            } => {
+                // A missing initializer is not reported, the variable is set later.
+                let initial = match value {
+                    Some(expr) => self.infer(expr),
+                    None => Type::Any,
+                };
+                // Without an annotation the initializer's type is inferred. A `var`
+                // may later widen to `Any`, a `const` keeps its first value.
+                let declared = match annotation {
+                    Some(annotation) => self.annotated_type(annotation),
+                    None => initial.clone(),
+                };
+                if !declared.accepts(&initial) {
+                    self.error(
+                        name.line,
+                        format!(
+                            "'{}' is declared as {} but initialized with {}.",
+                            name.lexeme, declared, initial
+                        ),
+                    );
+                }
+                self.declare(&name.lexeme, declared, annotation.is_some() || *constant);
+            }
+            Statement::BlockStatement { stats, .. } => self.check_block(stats),
+            Statement::TryStatement {
+                body,
+                handler,
+                finally,
+                ..
+            } => {
+                self.check_stat(body);
+                if let Some((name, catch_body)) = handler {
+                    self.scopes.push(HashMap::new());
+                    self.declare(&name.lexeme, Type::Any, false);
+                    self.check_stat(catch_body);
+                    self.scopes.pop();
+                }
+                if let Some(finally_body) = finally {
+                    self.check_stat(finally_body);
+                }
+            }
+            Statement::MatchStatement { subject, arms, .. } => {
+                self.infer(subject);
+                for (pattern, body) in arms {
+                    self.scopes.push(HashMap::new());
+                    self.declare_bindings(pattern);
+                    self.check_stat(body);
+                    self.scopes.pop();
+                }
+            }
+            Statement::ImportStatement { name, .. } => {
+                if let Some(name) = name {
+                    self.declare(&name.lexeme, Type::Map, false);
+                }
+            }
+        }
+    }
+
+    fn annotated_type(&mut self, annotation: &Token) -> Type {
+        match Type::from_name(&annotation.lexeme) {
+            Some(declared) => declared,
+            None => {
+                self.error(
+                    annotation.line,
+                    format!("Unknown type '{}'.", annotation.lexeme),
+                );
+                Type::Any
+            }
+        }
+    }
+
+    fn declare_bindings(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Binding { name } => self.declare(&name.lexeme, Type::Any, false),
+            Pattern::List { elements, .. } => {
+                for element in elements {
+                    self.declare_bindings(element);
+                }
+            }
+            Pattern::Alternatives { patterns } => {
+                for alternative in patterns {
+                    self.declare_bindings(alternative);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // An inferred variable widens to `Any` when it gets a value of another type.
+    // The assignment may be in a match arm or catch block that does not always
+    // run, so the wider type is kept from then on.
+    fn check_assignment(&mut self, name: &Token, value: &Type) {
+        let Some(binding) = self.binding(&name.lexeme) else {
+            return;
+        };
+        if !binding.annotated {
+            if binding.declared != *value {
+                binding.declared = Type::Any;
+            }
+            return;
+        }
+        let declared = binding.declared.clone();
+        if !declared.accepts(value) {
+            self.error(
+                name.line,
+                format!(
+                    "Cannot assign {} to '{}', which is declared as {}.",
+                    value, name.lexeme, declared
+                ),
+            );
+        }
+    }
+
+    fn binary_type(&mut self, operator: &Token, left: Type, right: Type) -> Type {
+        match operator.token_type {
+            TokenType::EQUAL_EQUAL | TokenType::BANG_EQUAL => Type::Bool,
+            TokenType::PLUS => {
+                if left == Type::Any || right == Type::Any {
+                    return Type::Any;
+                }
+                if left == right && (left == Type::Number || left == Type::String) {
+                    return left;
+                }
+                self.error(
+                    operator.line,
+                    format!(
+                        "Operands of '+' must be two numbers or two strings, got {} and {}.",
+                        left, right
+                    ),
+                );
+                return Type::Any;
+            }
+            _ => {
+                if !left.is(Type::Number) || !right.is(Type::Number) {
+                    self.error(
+                        operator.line,
+                        format!(
+                            "Operands of '{}' must be numbers, got {} and {}.",
+                            operator.lexeme, left, right
+                        ),
+                    );
+                }
+                match operator.token_type {
+                    TokenType::LESS
+                    | TokenType::LESS_EQUAL
+                    | TokenType::GREATER
+                    | TokenType::GREATER_EQUAL => Type::Bool,
+                    _ => Type::Number,
+                }
+            }
+        }
+    }
+
+    fn infer(&mut self, expr: &Expression) -> Type {
+        match expr {
+            Expression::Literal { value, .. } => Type::of(value),
+            Expression::Grouping { expr, .. } => self.infer(expr),
+            Expression::Variable { variable } => self.lookup(&variable.lexeme),
+            Expression::Unary { operator, expr } => {
+                let operand = self.infer(expr);
+                if operator.token_type == TokenType::BANG {
+                    return Type::Bool;
+                }
+                if !operand.is(Type::Number) {
+                    self.error(
+                        operator.line,
+                        format!(
+                            "Operand of '{}' must be a number, got {}.",
+                            operator.lexeme, operand
+                        ),
+                    );
+                }
+                return Type::Number;
+            }
+            Expression::Binary {
+                operator,
+                left_expr,
+                right_expr,
+            } => {
+                let left = self.infer(left_expr);
+                let right = self.infer(right_expr);
+                return self.binary_type(operator, left, right);
+            }
+            Expression::Assignment { name, value } => {
+                let assigned = self.infer(value);
+                self.check_assignment(name, &assigned);
+                return assigned;
+            }
+            Expression::CompoundAssignment {
+                target,
+                operator,
+                value,
+            } => {
+                let current = self.infer(target);
+                let operand = self.infer(value);
+                // `a += b` checks like `a + b`. Every other compound operator is
+                // numeric, which `binary_type` handles the same way for all of them.
+                let mut binary_operator = operator.clone();
+                binary_operator.lexeme = operator.lexeme.trim_end_matches('=').to_string();
+                binary_operator.token_type = match operator.token_type {
+                    TokenType::PLUS_EQUAL => TokenType::PLUS,
+                    _ => TokenType::MINUS,
+                };
+                let result = self.binary_type(&binary_operator, current, operand);
+                if let Expression::Variable { variable } = &**target {
+                    self.check_assignment(variable, &result);
+                }
+                return result;
+            }
+            Expression::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.infer(condition);
+                let then_type = self.infer(then_branch);
+                let else_type = self.infer(else_branch);
+                if then_type == else_type {
+                    return then_type;
+                }
+                return Type::Any;
+            }
+            Expression::ListLiteral { elements, .. } => {
+                for element in elements {
+                    self.infer(element);
+                }
+                return Type::List;
+            }
+            Expression::MapLiteral { entries, .. } => {
+                for (key, value) in entries {
+                    self.infer(key);
+                    self.infer(value);
+                }
+                return Type::Map;
+            }
+            Expression::Index {
+                object,
+                index,
+                bracket,
+            } => {
+                let container = self.infer(object);
+                let position = self.infer(index);
+                self.check_index(bracket.line, &container, &position);
+                return Type::Any;
+            }
+            Expression::IndexAssignment {
+                object,
+                index,
+                value,
+            } => {
+                let container = self.infer(object);
+                let position = self.infer(index);
+                self.check_index(expr.error_line(), &container, &position);
+                return self.infer(value);
+            }
+            Expression::MethodCall {
+                object,
+                name,
+                arguments,
+                ..
+            } => {
+                let receiver = self.infer(object);
+                for argument in arguments {
+                    self.infer(argument);
+                }
+                if receiver == Type::Any {
+                    return Type::Any;
+                }
+                match method_signature(&receiver, &name.lexeme) {
+                    Some((arity, result)) => {
+                        if arguments.len() != arity {
+                            self.error(
+                                name.line,
+                                format!(
+                                    "'{}' expects {} arguments but got {}.",
+                                    name.lexeme,
+                                    arity,
+                                    arguments.len()
+                                ),
+                            );
+                        }
+                        return result;
+                    }
+                    None => {
+                        self.error(
+                            name.line,
+                            format!("Type {} has no method '{}'.", receiver, name.lexeme),
+                        );
+                        return Type::Any;
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_index(&mut self, line: i32, container: &Type, position: &Type) {
+        match container {
+            Type::List if !position.is(Type::Number) => {
+                self.error(line, format!("List index must be a number, got {}.", position))
+            }
+            Type::List | Type::Map | Type::Any => {}
+            _ => self.error(line, format!("Type {} cannot be indexed.", container)),
+        }
+    }
+}
+
+// Type checks a parsed program without running it. Returns every error found,
+// in source order.
+pub fn check_program(stats: &[Statement]) -> Vec<String> {
+    let mut checker = Checker {
+        scopes: vec![HashMap::new()],
+        errors: Vec::new(),
+    };
+    for stat in stats {
+        checker.check_stat(stat);
+    }
+    return checker.errors;
+}
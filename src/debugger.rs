@@ -0,0 +1,271 @@
+use lazy_static::lazy_static;
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+    process::exit,
+    sync::Mutex,
+};
+
+use crate::{
+    environment::get_scopes,
+    expr::{Expression, Literal, AST},
+    tokenizer::Tokenizer,
+};
+
+const HELP: &str = "Commands:
+  s, step          run until the next statement, entering blocks
+  n, next          run until the next statement in this block or an outer one
+  o, out           run until the current block is left
+  c, continue      run until the next breakpoint
+  b, break <line>  set a breakpoint
+  d, delete <line> remove a breakpoint
+  l, list          show the source around the current line
+  v, vars          show every visible variable, innermost scope first
+  p, print <expr>  evaluate an expression in the current scope
+  w, watch <expr>  evaluate an expression at every pause
+  q, quit          stop the program";
+
+#[derive(Clone, PartialEq)]
+enum StepMode {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+struct Debugger {
+    enabled: bool,
+    source: Vec<String>,
+    breakpoints: HashSet<i32>,
+    watches: Vec<String>,
+    mode: StepMode,
+    depth: usize,
+    // Block depth at the moment the last step command was given.
+    step_depth: usize,
+}
+
+impl Debugger {
+    fn new() -> Debugger {
+        Debugger {
+            enabled: false,
+            source: Vec::new(),
+            breakpoints: HashSet::new(),
+            watches: Vec::new(),
+            mode: StepMode::Continue,
+            depth: 0,
+            step_depth: 0,
+        }
+    }
+
+    fn should_pause(&self, line: i32) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.breakpoints.contains(&line) {
+            return true;
+        }
+        match self.mode {
+            StepMode::Continue => false,
+            StepMode::StepIn => true,
+            StepMode::StepOver => self.depth <= self.step_depth,
+            StepMode::StepOut => self.depth < self.step_depth,
+        }
+    }
+
+    fn resume(&mut self, mode: StepMode) {
+        self.mode = mode;
+        self.step_depth = self.depth;
+    }
+
+    fn source_line(&self, line: i32) -> String {
+        return self
+            .source
+            .get((line - 1) as usize)
+            .cloned()
+            .unwrap_or_default();
+    }
+}
+
+lazy_static! {
+    static ref DEBUGGER: Mutex<Debugger> = Mutex::new(Debugger::new());
+}
+
+pub fn start(source: &str) {
+    let mut dbg = DEBUGGER.lock().unwrap();
+    dbg.enabled = true;
+    dbg.source = source.lines().map(|l| l.to_string()).collect();
+    dbg.mode = StepMode::StepIn;
+    println!("Type 'help' for a list of commands.");
+}
+
+pub fn enter_block() {
+    DEBUGGER.lock().unwrap().depth += 1;
+}
+
+pub fn exit_block() {
+    DEBUGGER.lock().unwrap().depth -= 1;
+}
+
+pub fn before_statement(line: i32) {
+    if !DEBUGGER.lock().unwrap().should_pause(line) {
+        return;
+    }
+    pause(line);
+}
+
+fn describe(lit: &Literal) -> String {
+    match lit {
+        Literal::String(s) => format!("\"{}\"", s),
+        _ => format!("{}", lit),
+    }
+}
+
+fn parse(source: &str) -> Option<Expression> {
+    let mut tokenizer = Tokenizer::new();
+    if tokenizer.scan(source.to_string()) != 0 {
+        return None;
+    }
+
+    let mut ast = AST::new(tokenizer.get_tokens());
+    match ast.parse_expression() {
+        Ok(expr) => Some(expr),
+        Err(_) => {
+            println!("Expect expression.");
+            None
+        }
+    }
+}
+
+fn evaluate(source: &str) -> Option<Literal> {
+    let expr = parse(source)?;
+    match expr.accept() {
+        Ok(lit) => Some(lit),
+        Err(error) => {
+            println!("{}", error.message());
+            None
+        }
+    }
+}
+
+fn print_evaluation(source: &str) {
+    if let Some(lit) = evaluate(source) {
+        println!("{} = {}", source, describe(&lit));
+    }
+}
+
+fn print_location(line: i32) {
+    let text = DEBUGGER.lock().unwrap().source_line(line);
+    println!("[line {}] {}", line, text.trim());
+}
+
+fn print_listing(line: i32) {
+    let dbg = DEBUGGER.lock().unwrap();
+    let first = (line - 3).max(1);
+    let last = (line + 3).min(dbg.source.len() as i32);
+    for current in first..=last {
+        let marker = if current == line { "->" } else { "  " };
+        println!("{} {:>4} {}", marker, current, dbg.source_line(current));
+    }
+}
+
+fn print_scopes() {
+    let scopes = get_scopes();
+    let count = scopes.len();
+    for (idx, scope) in scopes.iter().enumerate() {
+        if idx + 1 == count {
+            println!("[global]");
+        } else {
+            println!("[block {}]", count - idx - 1);
+        }
+        for (name, value) in scope {
+            println!("  {} = {}", name, describe(value));
+        }
+    }
+}
+
+fn parse_line(arg: &str) -> Option<i32> {
+    match arg.parse::<i32>() {
+        Ok(line) if line > 0 => Some(line),
+        _ => {
+            println!("Expect a line number.");
+            None
+        }
+    }
+}
+
+fn pause(line: i32) {
+    print_location(line);
+    let watches = DEBUGGER.lock().unwrap().watches.clone();
+    for watch in &watches {
+        print_evaluation(watch);
+    }
+
+    loop {
+        print!("(lox) ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => {
+                // No more input, let the program run to completion.
+                DEBUGGER.lock().unwrap().enabled = false;
+                return;
+            }
+            Ok(_) => {}
+        }
+
+        let input = input.trim();
+        let (command, arg) = match input.split_once(char::is_whitespace) {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (input, ""),
+        };
+
+        match command {
+            "" => {}
+            "s" | "step" => {
+                DEBUGGER.lock().unwrap().resume(StepMode::StepIn);
+                return;
+            }
+            "n" | "next" => {
+                DEBUGGER.lock().unwrap().resume(StepMode::StepOver);
+                return;
+            }
+            "o" | "out" => {
+                DEBUGGER.lock().unwrap().resume(StepMode::StepOut);
+                return;
+            }
+            "c" | "continue" => {
+                DEBUGGER.lock().unwrap().resume(StepMode::Continue);
+                return;
+            }
+            "b" | "break" => {
+                if let Some(target) = parse_line(arg) {
+                    DEBUGGER.lock().unwrap().breakpoints.insert(target);
+                    println!("Breakpoint set at line {}.", target);
+                }
+            }
+            "d" | "delete" => {
+                if let Some(target) = parse_line(arg) {
+                    if DEBUGGER.lock().unwrap().breakpoints.remove(&target) {
+                        println!("Breakpoint at line {} removed.", target);
+                    } else {
+                        println!("No breakpoint at line {}.", target);
+                    }
+                }
+            }
+            "l" | "list" => print_listing(line),
+            "v" | "vars" => print_scopes(),
+            "p" | "print" => print_evaluation(arg),
+            "w" | "watch" => {
+                // A watch that does not parse would only repeat the error at every pause.
+                if parse(arg).is_some() {
+                    DEBUGGER.lock().unwrap().watches.push(arg.to_string());
+                    print_evaluation(arg);
+                }
+            }
+            "q" | "quit" => exit(0),
+            "h" | "help" => println!("{}", HELP),
+            _ => println!("Unknown command: {}", command),
+        }
+    }
+}
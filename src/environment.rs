@@ -1,11 +1,13 @@
 use lazy_static::lazy_static;
-use std::{collections::HashMap, process::exit, sync::{Arc, Mutex}};
+use std::{collections::{HashMap, HashSet}, process::exit, sync::{Arc, Mutex}};
 
-use crate::expr::Literal;
+use crate::expr::{evaluation_error, Literal, RuntimeError};
 
 #[derive(Clone)]
 struct State {
     space: HashMap<String, Literal>,
+    // Names in `space` that were declared with `const`.
+    constants: HashSet<String>,
     parent: Option<Box<State>>,
 }
 
@@ -13,22 +15,34 @@ impl State {
     pub fn new(par: Option<Box<State>>) -> State {
         State {
             space: HashMap::new(),
+            constants: HashSet::new(),
             parent: par,
         }
     }
 
-    pub fn define(&mut self, name: String, value: Literal) {
+    // A constant cannot be redeclared in its own scope, with `var` or `const`.
+    // Declaring the same name in an inner scope shadows it as usual.
+    pub fn define(&mut self, name: String, value: Literal) -> Result<(), RuntimeError> {
+        if self.constants.contains(&name) {
+            return evaluation_error(&format!("Cannot redeclare constant {}.", name));
+        }
         self.space.insert(name, value);
+        return Ok(());
+    }
+
+    pub fn define_const(&mut self, name: String, value: Literal) -> Result<(), RuntimeError> {
+        Self::define(self, name.clone(), value)?;
+        self.constants.insert(name);
+        return Ok(());
     }
 
-    pub fn get(&mut self, name: String) -> Literal {
+    pub fn get(&mut self, name: String) -> Result<Literal, RuntimeError> {
         if let Some(val) = self.space.get(&name) {
-            return val.clone();
+            return Ok(val.clone());
         }
         match self.parent.clone() {
             None => {
-            eprintln!("Undefined variable {}.", name);
-            exit(70);
+                return evaluation_error(&format!("Undefined variable {}.", name));
             }
             Some(mut par) => {
                 return par.get(name);
@@ -36,17 +50,32 @@ impl State {
         }
     }
 
-    pub fn assign(&mut self, name: String, value: Literal) {
+    pub fn assign(&mut self, name: String, value: Literal) -> Result<(), RuntimeError> {
         if let Some(val) = self.space.get(&name) {
+            if self.constants.contains(&name) {
+                return evaluation_error(&format!("Cannot assign to constant {}.", name));
+            }
             self.space.insert(name.clone(), value);
-            return;
+            return Ok(());
         }
         if let Some(ref mut par) = self.parent {
-            par.assign(name.clone(), value);
-            return;
+            return par.assign(name.clone(), value);
+        }
+        return evaluation_error(&format!("Undefined variable {}.", name.clone()));
+    }
+
+    fn collect_scopes(&self, scopes: &mut Vec<Vec<(String, Literal)>>) {
+        let mut vars: Vec<(String, Literal)> = self
+            .space
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        vars.sort_by(|a, b| a.0.cmp(&b.0));
+        scopes.push(vars);
+
+        if let Some(par) = &self.parent {
+            par.collect_scopes(scopes);
         }
-        eprintln!("Undefined variable {}.", name.clone());
-        exit(70);
     }
 
     pub fn get_parent(&self) -> State {
@@ -66,17 +95,22 @@ lazy_static! {
     static ref STATE: Mutex<State> = Mutex::new(State::new(None));
 }
 
-pub fn define_env(name: String, value: Literal) {
+pub fn define_env(name: String, value: Literal) -> Result<(), RuntimeError> {
     let mut space = STATE.lock().unwrap();
-    space.define(name, value);
+    return space.define(name, value);
 }
 
-pub fn assign_env(name: String, value: Literal) {
+pub fn define_const_env(name: String, value: Literal) -> Result<(), RuntimeError> {
     let mut space = STATE.lock().unwrap();
-    space.assign(name, value);
+    return space.define_const(name, value);
 }
 
-pub fn get_env(name: String) -> Literal {
+pub fn assign_env(name: String, value: Literal) -> Result<(), RuntimeError> {
+    let mut space = STATE.lock().unwrap();
+    return space.assign(name, value);
+}
+
+pub fn get_env(name: String) -> Result<Literal, RuntimeError> {
     let mut space = STATE.lock().unwrap();
     return space.get(name);
 }
@@ -92,3 +126,35 @@ pub fn remove_block_scoping() {
     let prev_state = state.get_parent();
     *STATE.lock().unwrap() = prev_state;
 }
+
+// Runs `run` against a fresh top-level environment, as a module does, then puts
+// the caller's environment back. Returns the top-level variables sorted by name,
+// each with whether it was declared `const`.
+pub fn with_module_env<F>(run: F) -> Result<Vec<(String, Literal, bool)>, RuntimeError>
+where
+    F: FnOnce() -> Result<(), RuntimeError>,
+{
+    let saved = std::mem::replace(&mut *STATE.lock().unwrap(), State::new(None));
+    let result = run();
+    let module = std::mem::replace(&mut *STATE.lock().unwrap(), saved);
+    result?;
+
+    let mut vars: Vec<(String, Literal, bool)> = module
+        .space
+        .into_iter()
+        .map(|(name, value)| {
+            let constant = module.constants.contains(&name);
+            (name, value, constant)
+        })
+        .collect();
+    vars.sort_by(|a, b| a.0.cmp(&b.0));
+    return Ok(vars);
+}
+
+// Snapshot of every scope in the chain, innermost first, with variables sorted by name.
+pub fn get_scopes() -> Vec<Vec<(String, Literal)>> {
+    let state = STATE.lock().unwrap();
+    let mut scopes = Vec::new();
+    state.collect_scopes(&mut scopes);
+    return scopes;
+}
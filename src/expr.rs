@@ -1,26 +1,218 @@
 use std::{
+    cmp,
     fmt::Display,
     process::{exit, ExitCode},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 use crate::{
     environment::{assign_env, define_env, get_env},
-    token::{Token, TokenType},
+    list::{self, format_list, new_list, ListRef},
+    map::{self, format_map, new_map, MapRef},
+    token::{Span, Token, TokenType},
 };
 
-fn evaluation_error(msg: &str) -> Literal {
-    eprintln!("{}", msg);
-    exit(70)
+// When set, numeric strings are accepted wherever a number is expected, so
+// `-"3"` is -3 and `"2" < "10"` compares numerically.
+static LENIENT_COERCION: AtomicBool = AtomicBool::new(false);
+
+pub fn set_lenient_coercion(enabled: bool) {
+    LENIENT_COERCION.store(enabled, Ordering::Relaxed);
+}
+
+// Runtime representation of a number, shared by every printer. Integral values
+// drop the fraction, very large or small magnitudes switch to exponent form, and
+// non-finite values follow jlox.
+pub fn format_number(n: f64) -> String {
+    if n.is_nan() {
+        return "NaN".to_string();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    let magnitude = n.abs();
+    if magnitude >= 1e21 || (magnitude != 0.0 && magnitude < 1e-6) {
+        return format!("{:e}", n);
+    }
+    if n.fract() == 0.0 {
+        return format!("{:.0}", n);
+    }
+    return n.to_string();
+}
+
+// Numbers as they appear in `tokenize` and `parse` output, which always carry a
+// fractional part (`1.0` rather than `1`).
+pub fn format_number_literal(n: f64) -> String {
+    let mut formatted = format_number(n);
+    if n.is_finite() && !formatted.contains(['.', 'e']) {
+        formatted.push_str(".0");
+    }
+    return formatted;
+}
+
+// A runtime error unwinding towards the nearest `try` statement. Built-in errors
+// only carry a message, while `throw` can raise any value. The line is filled in
+// by the innermost expression or statement the error passes through, and `file`
+// by the imported module it was raised in, if any.
+#[derive(Clone)]
+pub enum RuntimeError {
+    Error {
+        message: String,
+        line: Option<i32>,
+        file: Option<String>,
+    },
+    Thrown {
+        value: Literal,
+        line: Option<i32>,
+        file: Option<String>,
+    },
+}
+
+impl RuntimeError {
+    pub fn line(&self) -> Option<i32> {
+        match self {
+            RuntimeError::Error { line, .. } => *line,
+            RuntimeError::Thrown { line, .. } => *line,
+        }
+    }
+
+    pub fn with_line(self, line: i32) -> RuntimeError {
+        match self {
+            RuntimeError::Error {
+                message,
+                line: None,
+                file,
+            } => RuntimeError::Error {
+                message,
+                line: Some(line),
+                file,
+            },
+            RuntimeError::Thrown {
+                value,
+                line: None,
+                file,
+            } => RuntimeError::Thrown {
+                value,
+                line: Some(line),
+                file,
+            },
+            error => error,
+        }
+    }
+
+    // Marks an error as raised inside the module at `path`, unless a module it
+    // imported already claimed it.
+    pub fn in_module(self, path: &str) -> RuntimeError {
+        match self {
+            RuntimeError::Error {
+                message,
+                line,
+                file: None,
+            } => RuntimeError::Error {
+                message,
+                line,
+                file: Some(path.to_string()),
+            },
+            RuntimeError::Thrown {
+                value,
+                line,
+                file: None,
+            } => RuntimeError::Thrown {
+                value,
+                line,
+                file: Some(path.to_string()),
+            },
+            error => error,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            RuntimeError::Error { message, .. } => message.clone(),
+            RuntimeError::Thrown { value, .. } => format!("Uncaught exception: {}", value),
+        }
+    }
+
+    // The value bound by `catch (e)`. Built-in errors become a map with the
+    // message and the line they were raised on. String keys always hash, so
+    // building the map cannot fail.
+    pub fn to_value(&self) -> Literal {
+        match self {
+            RuntimeError::Error { message, line, .. } => new_map(vec![
+                (
+                    Literal::String("message".to_string()),
+                    Literal::String(message.clone()),
+                ),
+                (
+                    Literal::String("line".to_string()),
+                    match line {
+                        Some(line) => Literal::Int(*line as i64),
+                        None => Literal::Nil,
+                    },
+                ),
+            ])
+            .unwrap_or_else(|_| unreachable!()),
+            RuntimeError::Thrown { value, .. } => value.clone(),
+        }
+    }
+
+    // Reports an error nothing caught and stops the interpreter. Lox has no
+    // functions yet, so the trace only holds the file the error was raised in.
+    pub fn report(&self) -> ! {
+        eprintln!("{}", self.message());
+        let file = match self {
+            RuntimeError::Error { file, .. } | RuntimeError::Thrown { file, .. } => file,
+        };
+        if let Some(line) = self.line() {
+            eprintln!("[line {}] in {}", line, file.as_deref().unwrap_or("script"));
+        }
+        exit(70)
+    }
+}
+
+pub fn evaluation_error<T>(msg: &str) -> Result<T, RuntimeError> {
+    return Err(RuntimeError::Error {
+        message: msg.to_string(),
+        line: None,
+        file: None,
+    });
+}
+
+// Literals without a fraction are integers, unless they are too large for one.
+pub fn number_literal(token: &Token) -> Literal {
+    let literal = token.literal.clone().unwrap_or_default();
+    match literal.parse::<i64>() {
+        Ok(n) => Literal::Int(n),
+        Err(_) => Literal::Number(literal.parse::<f64>().unwrap_or_default()),
+    }
+}
+
+fn checked_int(result: Option<i64>) -> Result<Literal, RuntimeError> {
+    match result {
+        Some(n) => Ok(Literal::Int(n)),
+        None => evaluation_error("Integer overflow."),
+    }
 }
 
 /**
  * Grammer
- * expression     → literal | unary | binary | grouping ;
- * literal        → NUMBER | STRING | "true" | "false" | "nil" ;
+ * expression     → literal | unary | binary | grouping | conditional ;
+ * conditional    → expression "?" expression ":" expression ;
+ * literal        → NUMBER | STRING | "true" | "false" | "nil" | list ;
+ * list           → "[" ( expression ( "," expression )* ","? )? "]" ;
+ * map            → "{" ( entry ( "," entry )* ","? )? "}" ;
+ * entry          → expression ":" expression ;
+ * index          → expression "[" expression "]" ;
+ * method_call    → expression "." IDENTIFIER "(" ( expression ( "," expression )* )? ")" ;
  * grouping       → "(" expression ")" ;
- * unary          → ( "-" | "!" ) expression ;
+ * unary          → ( "-" | "!" | "~" ) expression ;
  * binary         → expression operator expression ;
- * operator       → "==" | "!=" | "<" | "<=" | ">" | ">=" | "+"  | "-"  | "*" | "/" ;
+ * operator       → "==" | "!=" | "<" | "<=" | ">" | ">=" | "+"  | "-"  | "*" | "/" | "~/" | "%"
+ *                | "**" | "&" | "|" | "^" | "<<" | ">>" ;
  **/
 
 #[derive(Clone)]
@@ -28,12 +220,18 @@ pub enum Literal {
     Bool(bool),
     Nil,
     Number(f64),
+    Int(i64),
     String(String),
+    List(ListRef),
+    Map(MapRef),
 }
 
 #[derive(Clone)]
 pub enum Expression {
-    Literal(Literal),
+    Literal {
+        value: Literal,
+        span: Span,
+    },
     Unary {
         operator: Token,
         expr: Box<Expression>,
@@ -45,6 +243,7 @@ pub enum Expression {
     },
     Grouping {
         expr: Box<Expression>,
+        span: Span,
     },
     Variable {
         variable: Token,
@@ -53,81 +252,152 @@ pub enum Expression {
         name: Token,
         value: Box<Expression>,
     },
+    CompoundAssignment {
+        target: Box<Expression>,
+        operator: Token,
+        value: Box<Expression>,
+    },
+    Conditional {
+        condition: Box<Expression>,
+        then_branch: Box<Expression>,
+        else_branch: Box<Expression>,
+    },
+    ListLiteral {
+        elements: Vec<Expression>,
+        span: Span,
+    },
+    MapLiteral {
+        entries: Vec<(Expression, Expression)>,
+        span: Span,
+    },
+    Index {
+        object: Box<Expression>,
+        index: Box<Expression>,
+        // The closing bracket.
+        bracket: Token,
+    },
+    IndexAssignment {
+        object: Box<Expression>,
+        index: Box<Expression>,
+        value: Box<Expression>,
+    },
+    MethodCall {
+        object: Box<Expression>,
+        name: Token,
+        arguments: Vec<Expression>,
+        // The closing parenthesis.
+        paren: Token,
+    },
 }
 
 impl Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Literal::String(b) => f.write_fmt(format_args!("{b}")),
-            Literal::Nil => f.write_str("nil"),
-            Literal::Number(n) => f.write_fmt(format_args!("{n:?}")),
-            Literal::Bool(s) => f.write_fmt(format_args!("{s}")),
+        f.write_str(&self.to_lox_string())
+    }
+}
+
+// The integer an integral float stands for, when it is in i64 range. Such a
+// float converts exactly, unlike an i64 above 2^53 converted to a float.
+pub fn integral_float(n: f64) -> Option<i64> {
+    if n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64 {
+        return Some(n as i64);
+    }
+    return None;
+}
+
+// Orders an integer against a float without rounding the integer, so
+// `9007199254740993 == 9007199254740992.0` is false. None when `f` is NaN.
+fn compare_int_float(i: i64, f: f64) -> Option<cmp::Ordering> {
+    if f.is_nan() {
+        return None;
+    }
+    // i64::MIN as f64 is exactly -2^63, and every i64 is below 2^63.
+    if f >= -(i64::MIN as f64) {
+        return Some(cmp::Ordering::Less);
+    }
+    if f < i64::MIN as f64 {
+        return Some(cmp::Ordering::Greater);
+    }
+    match i.cmp(&(f.trunc() as i64)) {
+        cmp::Ordering::Equal => 0.0.partial_cmp(&f.fract()),
+        ordering => Some(ordering),
+    }
+}
+
+// Lox equality: values of different types are never equal, numbers compare by
+// IEEE value (so NaN != NaN), strings by content and lists and maps by identity.
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Literal::Nil, Literal::Nil) => true,
+            (Literal::Bool(l), Literal::Bool(r)) => l == r,
+            (Literal::Number(l), Literal::Number(r)) => l == r,
+            (Literal::Int(l), Literal::Int(r)) => l == r,
+            (Literal::Int(l), Literal::Number(r)) => compare_int_float(*l, *r) == Some(cmp::Ordering::Equal),
+            (Literal::Number(l), Literal::Int(r)) => compare_int_float(*r, *l) == Some(cmp::Ordering::Equal),
+            (Literal::String(l), Literal::String(r)) => l == r,
+            (Literal::List(l), Literal::List(r)) => Arc::ptr_eq(l, r),
+            (Literal::Map(l), Literal::Map(r)) => Arc::ptr_eq(l, r),
+            _ => false,
         }
     }
 }
 
 impl Literal {
-    fn get_type(&self) -> String {
+    pub fn get_type(&self) -> String {
         match self {
             Literal::String(_s) => "string".to_string(),
             Literal::Nil => "nil".to_string(),
             Literal::Number(_n) => "number".to_string(),
+            Literal::Int(_n) => "number".to_string(),
             Literal::Bool(_b) => "bool".to_string(),
+            Literal::List(_l) => "list".to_string(),
+            Literal::Map(_m) => "map".to_string(),
         }
     }
 
-    fn to_string(&self) -> Result<String, String> {
+    // The text a value turns into when printed or concatenated.
+    pub fn to_lox_string(&self) -> String {
         match self {
-            Literal::String(s) => Ok(s.clone()),
-            Literal::Nil => Err("Error type".to_string()),
-            Literal::Number(n) => Ok(n.to_string()),
-            Literal::Bool(b) => Ok(if *b {
-                "true".to_string()
-            } else {
-                "false".to_string()
-            }),
+            Literal::String(s) => s.clone(),
+            Literal::Nil => "nil".to_string(),
+            Literal::Number(n) => format_number(*n),
+            Literal::Int(n) => n.to_string(),
+            Literal::Bool(b) => b.to_string(),
+            Literal::List(items) => format_list(items, &mut Vec::new()),
+            Literal::Map(entries) => format_map(entries, &mut Vec::new()),
         }
     }
 
     fn to_number(&self) -> Result<f64, String> {
         match self {
-            Literal::String(s) => {
+            Literal::String(s) if LENIENT_COERCION.load(Ordering::Relaxed) => {
                 let num = s.parse();
                 match num {
                     Ok(n) => Ok(n),
                     Err(_e) => Err("Error type".to_string()),
                 }
             }
-            Literal::Nil => Err("Error type".to_string()),
-            Literal::Bool(_b) => Err("Error type".to_string()),
             Literal::Number(n) => Ok(*n),
+            Literal::Int(n) => Ok(*n as f64),
+            _ => Err("Error type".to_string()),
         }
     }
 
-    fn to_bool(&self) -> Result<bool, String> {
+    pub fn to_bool(&self) -> Result<bool, String> {
         match self {
             Literal::String(_s) => Ok(true),
             Literal::Nil => Ok(false),
             Literal::Number(_n) => Ok(true),
+            Literal::Int(_n) => Ok(true),
             Literal::Bool(b) => Ok(*b),
+            Literal::List(_l) => Ok(true),
+            Literal::Map(_m) => Ok(true),
         }
     }
 
     pub fn print(&self) {
-        match self {
-            Literal::String(s) => println!("{}", s),
-            Literal::Nil => println!("nil"),
-            Literal::Bool(s) => println!("{}", s),
-            Literal::Number(n) => {
-                let _formatted = if n.fract() == 0.0 {
-                    // If there is no fractional part, show one decimal place
-                    println!("{:.0}", n)
-                } else {
-                    // Otherwise, show the full precision
-                    println!("{}", n)
-                };
-            }
-        }
+        println!("{}", self.to_lox_string());
     }
 }
 
@@ -145,27 +415,93 @@ impl Display for Expression {
                 "({} {left_expr} {right_expr})",
                 operator.lexeme
             )),
-            Expression::Grouping { expr } => f.write_fmt(format_args!("(group {expr})")),
-            Expression::Literal(lit) => f.write_fmt(format_args!("{}", lit)),
+            Expression::Grouping { expr, .. } => f.write_fmt(format_args!("(group {expr})")),
+            Expression::Literal { value, .. } => match value {
+                Literal::Number(n) => f.write_str(&format_number_literal(*n)),
+                Literal::Int(n) => f.write_fmt(format_args!("{}.0", n)),
+                _ => f.write_fmt(format_args!("{}", value)),
+            },
             Expression::Variable { variable } => f.write_fmt(format_args!("{}", variable.lexeme)),
-            Expression::Assignment { name, value } => f.write_fmt(format_args!("{}", name.lexeme)),
+            Expression::Assignment { name, value } => {
+                f.write_fmt(format_args!("(= {} {value})", name.lexeme))
+            }
+            Expression::CompoundAssignment {
+                target,
+                operator,
+                value,
+            } => f.write_fmt(format_args!("({} {target} {value})", operator.lexeme)),
+            Expression::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => f.write_fmt(format_args!("(?: {condition} {then_branch} {else_branch})")),
+            Expression::ListLiteral { elements, .. } => {
+                f.write_str("(list")?;
+                for element in elements {
+                    f.write_fmt(format_args!(" {element}"))?;
+                }
+                f.write_str(")")
+            }
+            Expression::MapLiteral { entries, .. } => {
+                f.write_str("(map")?;
+                for (key, value) in entries {
+                    f.write_fmt(format_args!(" ({key} {value})"))?;
+                }
+                f.write_str(")")
+            }
+            Expression::Index { object, index, .. } => {
+                f.write_fmt(format_args!("(index {object} {index})"))
+            }
+            Expression::IndexAssignment {
+                object,
+                index,
+                value,
+            } => f.write_fmt(format_args!("(= (index {object} {index}) {value})")),
+            Expression::MethodCall {
+                object,
+                name,
+                arguments,
+                ..
+            } => {
+                f.write_fmt(format_args!("(.{} {object}", name.lexeme))?;
+                for argument in arguments {
+                    f.write_fmt(format_args!(" {argument}"))?;
+                }
+                f.write_str(")")
+            }
         }
     }
 }
 
-pub fn eval_unary(operator: Token, expr: &Expression) -> Literal {
-    let expr_lit_raw = expr.accept();
+pub fn eval_unary(operator: Token, expr: &Expression) -> Result<Literal, RuntimeError> {
+    let expr_lit_raw = expr.accept()?;
 
     match operator.token_type {
-        TokenType::MINUS => match expr_lit_raw.to_number() {
-            Ok(num) => {
-                return Literal::Number(-num);
+        TokenType::MINUS => {
+            // Integers have no negative zero, so `-0` becomes the float -0.0 and
+            // keeps its sign like in jlox.
+            if let Literal::Int(n) = expr_lit_raw {
+                if n == 0 {
+                    return Ok(Literal::Number(-0.0));
+                }
+                return checked_int(n.checked_neg());
             }
-            Err(_) => evaluation_error("Operand must be a number."),
+            match expr_lit_raw.to_number() {
+                Ok(num) => {
+                    return Ok(Literal::Number(-num));
+                }
+                Err(_) => evaluation_error("Operand must be a number."),
+            }
+        }
+        TokenType::TILDE => match expr_lit_raw {
+            Literal::Int(n) => {
+                return Ok(Literal::Int(!n));
+            }
+            _ => evaluation_error("Operand must be an integer."),
         },
         TokenType::BANG => match expr_lit_raw.to_bool() {
             Ok(b) => {
-                return Literal::Bool(!b);
+                return Ok(Literal::Bool(!b));
             }
             Err(_) => panic!("Something went wrong!"),
         },
@@ -173,97 +509,115 @@ pub fn eval_unary(operator: Token, expr: &Expression) -> Literal {
     }
 }
 
-// "==" | "!=" | "<" | "<=" | ">" | ">=" | "+"  | "-"  | "*" | "/" ;
-pub fn eval_binary(operator: &Token, left_expr: &Expression, right_expr: &Expression) -> Literal {
-    let left_raw = left_expr.accept();
-    let right_raw = right_expr.accept();
+// "==" | "!=" | "<" | "<=" | ">" | ">=" | "+"  | "-"  | "*" | "/" | "~/" | "%"
+// | "**" | "&" | "|" | "^" | "<<" | ">>" ;
+pub fn eval_binary(operator: &Token, left_expr: &Expression, right_expr: &Expression) -> Result<Literal, RuntimeError> {
+    let left_raw = left_expr.accept()?;
+    let right_raw = right_expr.accept()?;
+    return apply_binary(&operator.token_type, left_raw, right_raw);
+}
+
+fn apply_binary(operator: &TokenType, left_raw: Literal, right_raw: Literal) -> Result<Literal, RuntimeError> {
+    // Two integers stay integers, any other numeric pair is promoted to floats.
+    let ints = match (&left_raw, &right_raw) {
+        (Literal::Int(l), Literal::Int(r)) => Some((*l, *r)),
+        _ => None,
+    };
     let left = left_raw.to_number();
     let right = right_raw.to_number();
-    let left_str = left_raw.to_string();
-    let right_str = right_raw.to_string();
     let left_type = left_raw.get_type();
     let right_type = right_raw.get_type();
+    // Mixed int and float pairs are ordered exactly, the same way `==` compares them.
+    let order = match (&left_raw, &right_raw) {
+        (Literal::Int(l), Literal::Int(r)) => Some(l.cmp(r)),
+        (Literal::Int(l), Literal::Number(r)) => compare_int_float(*l, *r),
+        (Literal::Number(l), Literal::Int(r)) => compare_int_float(*r, *l).map(cmp::Ordering::reverse),
+        _ => match (&left, &right) {
+            (Ok(l), Ok(r)) => l.partial_cmp(r),
+            _ => None,
+        },
+    };
 
-    match operator.token_type {
+    match operator {
         TokenType::EQUAL_EQUAL => {
-            if let Ok(l) = left_str {
-                if let Ok(r) = right_str {
-                    return Literal::Bool(l == r && left_type == right_type);
-                }
-            }
-            return evaluation_error("Operands must be numbers.");
+            return Ok(Literal::Bool(left_raw == right_raw));
         }
         TokenType::BANG_EQUAL => {
-            if let Ok(l) = left_str {
-                if let Ok(r) = right_str {
-                    return Literal::Bool(l != r || left_type != right_type);
-                }
-            }
-            return evaluation_error("Operands must be numbers.");
+            return Ok(Literal::Bool(left_raw != right_raw));
         }
         TokenType::LESS => {
-            if let Ok(l) = left {
-                if let Ok(r) = right {
-                    return Literal::Bool(l < r);
-                }
+            if left.is_err() || right.is_err() {
+                return evaluation_error("Operands must be numbers.");
             }
-            return evaluation_error("Operands must be numbers.");
+            return Ok(Literal::Bool(order == Some(cmp::Ordering::Less)));
         }
         TokenType::GREATER => {
-            if let Ok(l) = left {
-                if let Ok(r) = right {
-                    return Literal::Bool(l > r);
-                }
+            if left.is_err() || right.is_err() {
+                return evaluation_error("Operands must be numbers.");
             }
-            return evaluation_error("Operands must be numbers.");
+            return Ok(Literal::Bool(order == Some(cmp::Ordering::Greater)));
         }
         TokenType::LESS_EQUAL => {
-            if let Ok(l) = left {
-                if let Ok(r) = right {
-                    return Literal::Bool(l <= r);
-                }
+            if left.is_err() || right.is_err() {
+                return evaluation_error("Operands must be numbers.");
             }
-            return evaluation_error("Operands must be numbers.");
+            return Ok(Literal::Bool(matches!(
+                order,
+                Some(cmp::Ordering::Less | cmp::Ordering::Equal)
+            )));
         }
         TokenType::GREATER_EQUAL => {
-            if let Ok(l) = left {
-                if let Ok(r) = right {
-                    return Literal::Bool(l >= r);
-                }
+            if left.is_err() || right.is_err() {
+                return evaluation_error("Operands must be numbers.");
             }
-            return evaluation_error("Operands must be numbers.");
+            return Ok(Literal::Bool(matches!(
+                order,
+                Some(cmp::Ordering::Greater | cmp::Ordering::Equal)
+            )));
         }
         TokenType::PLUS => {
             if left_type != right_type {
                 return evaluation_error("Operands must be two numbers or two strings.");
             }
 
+            if let Some((l, r)) = ints {
+                return checked_int(l.checked_add(r));
+            }
+
             if let Ok(l) = left {
                 if let Ok(r) = right {
-                    return Literal::Number(l + r);
+                    return Ok(Literal::Number(l + r));
                 }
             }
 
-            if let Ok(l_str) = left_str {
-                if let Ok(r_str) = right_str {
-                    return Literal::String(format!("{}{}", l_str, r_str));
-                }
+            if let (Literal::String(_), Literal::String(_)) = (&left_raw, &right_raw) {
+                return Ok(Literal::String(format!(
+                    "{}{}",
+                    left_raw.to_lox_string(),
+                    right_raw.to_lox_string()
+                )));
             }
 
             return evaluation_error("Operands must be two numbers or two strings.");
         }
         TokenType::MINUS => {
+            if let Some((l, r)) = ints {
+                return checked_int(l.checked_sub(r));
+            }
             if let Ok(l) = left {
                 if let Ok(r) = right {
-                    return Literal::Number(l - r);
+                    return Ok(Literal::Number(l - r));
                 }
             }
             return evaluation_error("Operands must be numbers.");
         }
         TokenType::STAR => {
+            if let Some((l, r)) = ints {
+                return checked_int(l.checked_mul(r));
+            }
             if let Ok(l) = left {
                 if let Ok(r) = right {
-                    return Literal::Number(l * r);
+                    return Ok(Literal::Number(l * r));
                 }
             }
             return evaluation_error("Operands must be numbers.");
@@ -271,10 +625,93 @@ pub fn eval_binary(operator: &Token, left_expr: &Expression, right_expr: &Expres
         TokenType::SLASH => {
             if let Ok(l) = left {
                 if let Ok(r) = right {
-                    if r == 0.0 {
-                        panic!("Something went wrong!");
+                    // Dividing by zero follows IEEE 754, giving infinity or NaN.
+                    return Ok(Literal::Number(l / r));
+                }
+            }
+            return evaluation_error("Operands must be numbers.");
+        }
+        TokenType::TILDE_SLASH => {
+            if let Some((l, r)) = ints {
+                if r == 0 {
+                    return evaluation_error("Division by zero.");
+                }
+                return checked_int(l.checked_div(r));
+            }
+            if let Ok(l) = left {
+                if let Ok(r) = right {
+                    return Ok(Literal::Number((l / r).trunc()));
+                }
+            }
+            return evaluation_error("Operands must be numbers.");
+        }
+        TokenType::STAR_STAR => {
+            if let Some((l, r)) = ints {
+                // Negative exponents fall through to the float path.
+                if r >= 0 {
+                    // These bases never overflow, however large the exponent.
+                    match l {
+                        0 => return Ok(Literal::Int(if r == 0 { 1 } else { 0 })),
+                        1 => return Ok(Literal::Int(1)),
+                        -1 => return Ok(Literal::Int(if r % 2 == 0 { 1 } else { -1 })),
+                        _ => {}
                     }
-                    return Literal::Number(l / r);
+                    return checked_int(u32::try_from(r).ok().and_then(|exp| l.checked_pow(exp)));
+                }
+            }
+            if let Ok(l) = left {
+                if let Ok(r) = right {
+                    return Ok(Literal::Number(l.powf(r)));
+                }
+            }
+            return evaluation_error("Operands must be numbers.");
+        }
+        TokenType::AMPERSAND => {
+            if let Some((l, r)) = ints {
+                return Ok(Literal::Int(l & r));
+            }
+            return evaluation_error("Operands must be integers.");
+        }
+        TokenType::PIPE => {
+            if let Some((l, r)) = ints {
+                return Ok(Literal::Int(l | r));
+            }
+            return evaluation_error("Operands must be integers.");
+        }
+        TokenType::CARET => {
+            if let Some((l, r)) = ints {
+                return Ok(Literal::Int(l ^ r));
+            }
+            return evaluation_error("Operands must be integers.");
+        }
+        TokenType::LESS_LESS => {
+            if let Some((l, r)) = ints {
+                return match u32::try_from(r).ok().and_then(|shift| l.checked_shl(shift)) {
+                    Some(n) => Ok(Literal::Int(n)),
+                    None => evaluation_error("Shift amount must be between 0 and 63."),
+                };
+            }
+            return evaluation_error("Operands must be integers.");
+        }
+        TokenType::GREATER_GREATER => {
+            if let Some((l, r)) = ints {
+                return match u32::try_from(r).ok().and_then(|shift| l.checked_shr(shift)) {
+                    Some(n) => Ok(Literal::Int(n)),
+                    None => evaluation_error("Shift amount must be between 0 and 63."),
+                };
+            }
+            return evaluation_error("Operands must be integers.");
+        }
+        TokenType::PERCENT => {
+            if let Some((l, r)) = ints {
+                if r == 0 {
+                    return evaluation_error("Division by zero.");
+                }
+                return checked_int(l.checked_rem(r));
+            }
+            if let Ok(l) = left {
+                if let Ok(r) = right {
+                    return Ok(Literal::Number(l % r));
                 }
             }
             return evaluation_error("Operands must be numbers.");
@@ -283,38 +720,209 @@ pub fn eval_binary(operator: &Token, left_expr: &Expression, right_expr: &Expres
     }
 }
 
-pub fn eval_group(expr: &Expression) -> Literal {
+pub fn eval_group(expr: &Expression) -> Result<Literal, RuntimeError> {
     return expr.accept();
 }
 
-pub fn eval_literal(lit: Literal) -> Literal {
-    return lit;
+pub fn eval_literal(lit: Literal) -> Result<Literal, RuntimeError> {
+    return Ok(lit);
 }
 
-pub fn eval_variable(variable: &Token) -> Literal {
+pub fn eval_variable(variable: &Token) -> Result<Literal, RuntimeError> {
     return get_env(variable.lexeme.clone());
 }
 
-pub fn eval_assignment(name: String, value: &Expression) -> Literal {
-    let val = value.accept();
-    assign_env(name, val.clone());
-    return val;
+pub fn eval_assignment(name: String, value: &Expression) -> Result<Literal, RuntimeError> {
+    let val = value.accept()?;
+    assign_env(name, val.clone())?;
+    return Ok(val);
+}
+
+pub fn eval_conditional(
+    condition: &Expression,
+    then_branch: &Expression,
+    else_branch: &Expression,
+) -> Result<Literal, RuntimeError> {
+    match condition.accept()?.to_bool() {
+        Ok(true) => then_branch.accept(),
+        Ok(false) => else_branch.accept(),
+        Err(_) => panic!("Something went wrong!"),
+    }
+}
+
+pub fn eval_list(elements: &[Expression]) -> Result<Literal, RuntimeError> {
+    let items = elements
+        .iter()
+        .map(|element| element.accept())
+        .collect::<Result<Vec<Literal>, RuntimeError>>()?;
+    return Ok(new_list(items));
+}
+
+pub fn eval_map(entries: &[(Expression, Expression)]) -> Result<Literal, RuntimeError> {
+    let entries = entries
+        .iter()
+        .map(|(key, value)| Ok((key.accept()?, value.accept()?)))
+        .collect::<Result<Vec<(Literal, Literal)>, RuntimeError>>()?;
+    return new_map(entries);
+}
+
+fn get_index(object: &Literal, index: &Literal) -> Result<Literal, RuntimeError> {
+    match object {
+        Literal::List(items) => list::get_index(items, index),
+        Literal::Map(entries) => map::get_entry(entries, index),
+        _ => evaluation_error("Only lists and maps can be indexed."),
+    }
+}
+
+fn set_index(object: &Literal, index: &Literal, value: Literal) -> Result<Literal, RuntimeError> {
+    match object {
+        Literal::List(items) => list::set_index(items, index, value),
+        Literal::Map(entries) => map::set_entry(entries, index, value),
+        _ => evaluation_error("Only lists and maps can be indexed."),
+    }
+}
+
+pub fn eval_index(object: &Expression, index: &Expression) -> Result<Literal, RuntimeError> {
+    let container = object.accept()?;
+    let position = index.accept()?;
+    return get_index(&container, &position);
+}
+
+pub fn eval_index_assignment(object: &Expression, index: &Expression, value: &Expression) -> Result<Literal, RuntimeError> {
+    let container = object.accept()?;
+    let position = index.accept()?;
+    let val = value.accept()?;
+    return set_index(&container, &position, val);
+}
+
+pub fn eval_method_call(object: &Expression, name: &Token, arguments: &[Expression]) -> Result<Literal, RuntimeError> {
+    let receiver = object.accept()?;
+    let args = arguments
+        .iter()
+        .map(|argument| argument.accept())
+        .collect::<Result<Vec<Literal>, RuntimeError>>()?;
+    match receiver {
+        Literal::List(items) => list::call_method(&items, name, args),
+        Literal::Map(entries) => map::call_method(&entries, name, args),
+        _ => evaluation_error("Only lists and maps have methods."),
+    }
+}
+
+pub fn eval_compound_assignment(target: &Expression, operator: &Token, value: &Expression) -> Result<Literal, RuntimeError> {
+    let binary_operator = match operator.token_type {
+        TokenType::PLUS_EQUAL => TokenType::PLUS,
+        TokenType::MINUS_EQUAL => TokenType::MINUS,
+        TokenType::STAR_EQUAL => TokenType::STAR,
+        TokenType::SLASH_EQUAL => TokenType::SLASH,
+        TokenType::PERCENT_EQUAL => TokenType::PERCENT,
+        _ => panic!("Something went wrong!"),
+    };
+
+    match target {
+        Expression::Variable { variable } => {
+            let current = get_env(variable.lexeme.clone())?;
+            let val = apply_binary(&binary_operator, current, value.accept()?)?;
+            assign_env(variable.lexeme.clone(), val.clone())?;
+            return Ok(val);
+        }
+        Expression::Index { object, index, .. } => {
+            // The container and index are evaluated once and reused for the read and the write.
+            let container = object.accept()?;
+            let position = index.accept()?;
+            let current = get_index(&container, &position)?;
+            let val = apply_binary(&binary_operator, current, value.accept()?)?;
+            return set_index(&container, &position, val);
+        }
+        _ => panic!("Something went wrong!"),
+    }
 }
 
 impl Expression {
-    pub fn accept(&self) -> Literal {
+    pub fn span(&self) -> Span {
         match self {
+            Expression::Literal { span, .. } => *span,
+            Expression::Unary { operator, expr } => operator.span().merge(expr.span()),
+            Expression::Binary {
+                left_expr,
+                right_expr,
+                ..
+            } => left_expr.span().merge(right_expr.span()),
+            Expression::Grouping { span, .. } => *span,
+            Expression::Variable { variable } => variable.span(),
+            Expression::Assignment { name, value } => name.span().merge(value.span()),
+            Expression::CompoundAssignment { target, value, .. } => {
+                target.span().merge(value.span())
+            }
+            Expression::Conditional {
+                condition,
+                else_branch,
+                ..
+            } => condition.span().merge(else_branch.span()),
+            Expression::ListLiteral { span, .. } => *span,
+            Expression::MapLiteral { span, .. } => *span,
+            Expression::Index {
+                object, bracket, ..
+            } => object.span().merge(bracket.span()),
+            Expression::IndexAssignment { object, value, .. } => {
+                object.span().merge(value.span())
+            }
+            Expression::MethodCall { object, paren, .. } => object.span().merge(paren.span()),
+        }
+    }
+
+    // The line reported when evaluating this node fails: the operator or name
+    // that caused the error where there is one, otherwise where the node starts.
+    pub fn error_line(&self) -> i32 {
+        match self {
+            Expression::Unary { operator, .. } => operator.line,
+            Expression::Binary { operator, .. } => operator.line,
+            Expression::Variable { variable } => variable.line,
+            Expression::Assignment { name, .. } => name.line,
+            Expression::CompoundAssignment { operator, .. } => operator.line,
+            Expression::Index { bracket, .. } => bracket.line,
+            Expression::MethodCall { name, .. } => name.line,
+            _ => self.span().line,
+        }
+    }
+
+    pub fn accept(&self) -> Result<Literal, RuntimeError> {
+        let result = match self {
             Expression::Binary {
                 operator,
                 left_expr,
                 right_expr,
             } => eval_binary(operator, &**left_expr, &**right_expr),
-            Expression::Grouping { expr } => eval_group(expr),
+            Expression::Grouping { expr, .. } => eval_group(expr),
             Expression::Unary { operator, expr } => eval_unary(operator.clone(), &**expr),
-            Expression::Literal(lit) => eval_literal(lit.clone()),
+            Expression::Literal { value, .. } => eval_literal(value.clone()),
             Expression::Variable { variable } => eval_variable(variable),
             Expression::Assignment { name, value } => eval_assignment(name.lexeme.clone(), &**value),
-        }
+            Expression::CompoundAssignment {
+                target,
+                operator,
+                value,
+            } => eval_compound_assignment(target, operator, value),
+            Expression::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => eval_conditional(condition, then_branch, else_branch),
+            Expression::ListLiteral { elements, .. } => eval_list(elements),
+            Expression::MapLiteral { entries, .. } => eval_map(entries),
+            Expression::Index { object, index, .. } => eval_index(object, index),
+            Expression::IndexAssignment {
+                object,
+                index,
+                value,
+            } => eval_index_assignment(object, index, value),
+            Expression::MethodCall {
+                object,
+                name,
+                arguments,
+                ..
+            } => eval_method_call(object, name, arguments),
+        };
+        return result.map_err(|error| error.with_line(self.error_line()));
     }
 }
 
@@ -322,6 +930,10 @@ pub struct AST {
     tokens: Vec<Token>,
     exprs: Vec<Expression>,
     curr_idx: usize,
+    // When set, a syntax error is recorded in `error` instead of ending the
+    // process, and parsing winds down by skipping to the end of the tokens.
+    recover: bool,
+    error: Option<String>,
 }
 
 impl AST {
@@ -330,9 +942,30 @@ impl AST {
             curr_idx: 0,
             tokens,
             exprs: Vec::new(),
+            recover: false,
+            error: None,
         };
     }
 
+    pub fn set_recover(&mut self, recover: bool) {
+        self.recover = recover;
+    }
+
+    pub fn take_error(&mut self) -> Option<String> {
+        return self.error.take();
+    }
+
+    pub fn syntax_error(&mut self, message: String) {
+        if !self.recover {
+            eprintln!("{}", message);
+            exit(65);
+        }
+        if self.error.is_none() {
+            self.error = Some(message);
+        }
+        self.curr_idx = self.tokens.len();
+    }
+
     pub fn advance(&mut self) {
         self.curr_idx += 1;
     }
@@ -345,7 +978,29 @@ impl AST {
     }
 
     pub fn peek(&self) -> Token {
-        return self.tokens.get(self.curr_idx).unwrap().clone();
+        match self.tokens.get(self.curr_idx) {
+            Some(token) => token.clone(),
+            // Past the last token, so errors at the end of the input have something to point at.
+            None => {
+                let (line, column, offset) = match self.tokens.last() {
+                    Some(last) => (
+                        last.line,
+                        last.column + last.lexeme.len() as i32,
+                        last.offset + last.lexeme.len(),
+                    ),
+                    None => (1, 1, 0),
+                };
+                Token::new(TokenType::EOF, String::new(), None, line, column, offset)
+            }
+        }
+    }
+
+    // Looks `distance` tokens past the current one without consuming anything.
+    pub fn check_ahead(&self, distance: usize, token_type: TokenType) -> bool {
+        match self.tokens.get(self.curr_idx + distance) {
+            Some(token) => token.token_type == token_type,
+            None => false,
+        }
     }
 
     pub fn check(&self, token_type: TokenType) -> bool {
@@ -367,8 +1022,8 @@ impl AST {
 
     pub fn consume(&mut self, expected_token: TokenType, error_msg: String) -> Token {
         if !Self::match_type(self, &[expected_token]) {
-            eprintln!("{}", error_msg);
-            exit(65);
+            Self::syntax_error(self, error_msg);
+            return Self::peek(self);
         }
 
         let token = Self::peek(self);
@@ -398,7 +1053,7 @@ impl AST {
     }
 
     fn comparision(&mut self) -> Expression {
-        let mut left_expr: Expression = Self::term(self);
+        let mut left_expr: Expression = Self::bit_or(self);
 
         let match_targets = [
             TokenType::LESS_EQUAL,
@@ -406,6 +1061,82 @@ impl AST {
             TokenType::GREATER,
             TokenType::GREATER_EQUAL,
         ];
+        while Self::match_type(self, &match_targets) {
+            let operator = Self::peek(self);
+            Self::advance(self);
+            let right_expr: Expression = Self::bit_or(self);
+
+            left_expr = Expression::Binary {
+                operator,
+                left_expr: Box::new(left_expr),
+                right_expr: Box::new(right_expr),
+            };
+        }
+
+        return left_expr;
+    }
+
+    fn bit_or(&mut self) -> Expression {
+        let mut left_expr: Expression = Self::bit_xor(self);
+
+        let match_targets = [TokenType::PIPE];
+        while Self::match_type(self, &match_targets) {
+            let operator = Self::peek(self);
+            Self::advance(self);
+            let right_expr: Expression = Self::bit_xor(self);
+
+            left_expr = Expression::Binary {
+                operator,
+                left_expr: Box::new(left_expr),
+                right_expr: Box::new(right_expr),
+            };
+        }
+
+        return left_expr;
+    }
+
+    fn bit_xor(&mut self) -> Expression {
+        let mut left_expr: Expression = Self::bit_and(self);
+
+        let match_targets = [TokenType::CARET];
+        while Self::match_type(self, &match_targets) {
+            let operator = Self::peek(self);
+            Self::advance(self);
+            let right_expr: Expression = Self::bit_and(self);
+
+            left_expr = Expression::Binary {
+                operator,
+                left_expr: Box::new(left_expr),
+                right_expr: Box::new(right_expr),
+            };
+        }
+
+        return left_expr;
+    }
+
+    fn bit_and(&mut self) -> Expression {
+        let mut left_expr: Expression = Self::shift(self);
+
+        let match_targets = [TokenType::AMPERSAND];
+        while Self::match_type(self, &match_targets) {
+            let operator = Self::peek(self);
+            Self::advance(self);
+            let right_expr: Expression = Self::shift(self);
+
+            left_expr = Expression::Binary {
+                operator,
+                left_expr: Box::new(left_expr),
+                right_expr: Box::new(right_expr),
+            };
+        }
+
+        return left_expr;
+    }
+
+    fn shift(&mut self) -> Expression {
+        let mut left_expr: Expression = Self::term(self);
+
+        let match_targets = [TokenType::LESS_LESS, TokenType::GREATER_GREATER];
         while Self::match_type(self, &match_targets) {
             let operator = Self::peek(self);
             Self::advance(self);
@@ -443,7 +1174,12 @@ impl AST {
     fn factor(&mut self) -> Expression {
         let mut left_expr: Expression = Self::unary(self);
 
-        let match_targets = [TokenType::STAR, TokenType::SLASH];
+        let match_targets = [
+            TokenType::STAR,
+            TokenType::SLASH,
+            TokenType::TILDE_SLASH,
+            TokenType::PERCENT,
+        ];
         while Self::match_type(self, &match_targets) {
             let operator = Self::peek(self);
             Self::advance(self);
@@ -460,7 +1196,7 @@ impl AST {
     }
 
     fn unary(&mut self) -> Expression {
-        let match_targets = [TokenType::MINUS, TokenType::BANG];
+        let match_targets = [TokenType::MINUS, TokenType::BANG, TokenType::TILDE];
         if Self::match_type(self, &match_targets) {
             let operator = Self::peek(self);
             Self::advance(self);
@@ -474,46 +1210,188 @@ impl AST {
             return expr;
         }
 
-        return Self::primary(self);
+        return Self::power(self);
+    }
+
+    fn power(&mut self) -> Expression {
+        let left_expr: Expression = Self::call(self);
+
+        if Self::match_type(self, &[TokenType::STAR_STAR]) {
+            let operator = Self::peek(self);
+            Self::advance(self);
+            // Parsing the right side as a unary makes `**` right-associative and
+            // lets `2 ** -1` through, while `-2 ** 2` still means `-(2 ** 2)`.
+            let right_expr: Expression = Self::unary(self);
+
+            return Expression::Binary {
+                operator,
+                left_expr: Box::new(left_expr),
+                right_expr: Box::new(right_expr),
+            };
+        }
+
+        return left_expr;
+    }
+
+    fn arguments(&mut self, closing: TokenType) -> Vec<Expression> {
+        let mut arguments = Vec::new();
+
+        while !Self::check(self, closing.clone()) {
+            arguments.push(Self::expression(self));
+            if !Self::match_type(self, &[TokenType::COMMA]) {
+                break;
+            }
+            Self::advance(self);
+        }
+
+        return arguments;
+    }
+
+    fn call(&mut self) -> Expression {
+        let mut expr: Expression = Self::primary(self);
+
+        loop {
+            if Self::match_type(self, &[TokenType::LEFT_BRACKET]) {
+                Self::advance(self);
+                let index = Self::expression(self);
+                let bracket = Self::consume(
+                    self,
+                    TokenType::RIGHT_BRACKET,
+                    "Expect ']' after index.".to_string(),
+                );
+
+                expr = Expression::Index {
+                    object: Box::new(expr),
+                    index: Box::new(index),
+                    bracket,
+                };
+            } else if Self::match_type(self, &[TokenType::DOT]) {
+                Self::advance(self);
+                let name = Self::consume(
+                    self,
+                    TokenType::IDENTIFIER,
+                    "Expect method name after '.'.".to_string(),
+                );
+                Self::consume(
+                    self,
+                    TokenType::LEFT_PAREN,
+                    "Expect '(' after method name.".to_string(),
+                );
+                let arguments = Self::arguments(self, TokenType::RIGHT_PAREN);
+                let paren = Self::consume(
+                    self,
+                    TokenType::RIGHT_PAREN,
+                    "Expect ')' after arguments.".to_string(),
+                );
+
+                expr = Expression::MethodCall {
+                    object: Box::new(expr),
+                    name,
+                    arguments,
+                    paren,
+                };
+            } else {
+                break;
+            }
+        }
+
+        return expr;
     }
 
     fn primary(&mut self) -> Expression {
         if Self::match_type(self, &[TokenType::FALSE]) {
+            let span = Self::peek(self).span();
             Self::advance(self);
-            return Expression::Literal(Literal::Bool(false));
+            return Expression::Literal {
+                value: Literal::Bool(false),
+                span,
+            };
         }
         if Self::match_type(self, &[TokenType::TRUE]) {
+            let span = Self::peek(self).span();
             Self::advance(self);
-            return Expression::Literal(Literal::Bool(true));
+            return Expression::Literal {
+                value: Literal::Bool(true),
+                span,
+            };
         }
         if Self::match_type(self, &[TokenType::NIL]) {
+            let span = Self::peek(self).span();
             Self::advance(self);
-            return Expression::Literal(Literal::Nil);
+            return Expression::Literal {
+                value: Literal::Nil,
+                span,
+            };
         }
         if Self::match_type(self, &[TokenType::STRING]) {
-            let lit_string = Self::peek(self).literal.unwrap_or_default();
+            let token = Self::peek(self);
+            let lit_string = token.literal.clone().unwrap_or_default();
             Self::advance(self);
-            return Expression::Literal(Literal::String(lit_string));
+            return Expression::Literal {
+                value: Literal::String(lit_string),
+                span: token.span(),
+            };
         }
         if Self::match_type(self, &[TokenType::NUMBER]) {
-            let literal_number = Self::peek(self)
-                .literal
-                .unwrap_or_default()
-                .parse::<f64>()
-                .unwrap_or_default();
+            let token = Self::peek(self);
+            let value = number_literal(&token);
             Self::advance(self);
-            return Expression::Literal(Literal::Number(literal_number));
+            return Expression::Literal {
+                value,
+                span: token.span(),
+            };
         }
         if Self::match_type(self, &[TokenType::LEFT_PAREN]) {
+            let left_paren = Self::peek(self);
             Self::advance(self);
             let expr = Self::expression(self);
-            Self::consume(
+            let right_paren = Self::consume(
                 self,
                 TokenType::RIGHT_PAREN,
                 "[line 1] Error at '{}': Expect expression.".to_string(),
             );
             return Expression::Grouping {
                 expr: Box::new(expr),
+                span: left_paren.span().merge(right_paren.span()),
+            };
+        }
+        if Self::match_type(self, &[TokenType::LEFT_BRACKET]) {
+            let left_bracket = Self::peek(self);
+            Self::advance(self);
+            // A trailing comma is allowed, so `arguments` stops at the bracket.
+            let elements = Self::arguments(self, TokenType::RIGHT_BRACKET);
+            let right_bracket = Self::consume(
+                self,
+                TokenType::RIGHT_BRACKET,
+                "Expect ']' after list elements.".to_string(),
+            );
+            return Expression::ListLiteral {
+                elements,
+                span: left_bracket.span().merge(right_bracket.span()),
+            };
+        }
+        if Self::match_type(self, &[TokenType::LEFT_BRACE]) {
+            let left_brace = Self::peek(self);
+            Self::advance(self);
+            let mut entries = Vec::new();
+            while !Self::check(self, TokenType::RIGHT_BRACE) {
+                let key = Self::expression(self);
+                Self::consume(self, TokenType::COLON, "Expect ':' after map key.".to_string());
+                let value = Self::expression(self);
+                entries.push((key, value));
+                if !Self::match_type(self, &[TokenType::COMMA]) {
+                    break;
+                }
+                Self::advance(self);
+            }
+            let right_brace = Self::consume(
+                self,
+                TokenType::RIGHT_BRACE,
+                "Expect '}' after map entries.".to_string(),
+            );
+            return Expression::MapLiteral {
+                entries,
+                span: left_brace.span().merge(right_brace.span()),
             };
         }
         if Self::match_type(self, &[TokenType::IDENTIFIER]) {
@@ -523,15 +1401,41 @@ impl AST {
             Self::advance(self);
             return variable;
         }
-        eprintln!(
-            "[line 1] Error at '{}': Expect expression.",
-            self.peek().lexeme
-        );
-        exit(65);
+        let token = self.peek();
+        let message = format!("[line {}] Error at '{}': Expect expression.", token.line, token.lexeme);
+        Self::syntax_error(self, message);
+        return Expression::Literal {
+            value: Literal::Nil,
+            span: self.peek().span(),
+        };
+    }
+
+    fn conditional(&mut self) -> Expression {
+        let condition = Self::equality(self);
+
+        if Self::match_type(self, &[TokenType::QUESTION]) {
+            Self::advance(self);
+            let then_branch = Self::expression(self);
+            Self::consume(
+                self,
+                TokenType::COLON,
+                "Expect ':' after then branch of conditional expression.".to_string(),
+            );
+            // Recursing on the else branch makes `a ? b : c ? d : e` group to the right.
+            let else_branch = Self::conditional(self);
+
+            return Expression::Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            };
+        }
+
+        return condition;
     }
 
     pub fn assignment(&mut self) -> Expression {
-        let expr = self.equality();
+        let expr = self.conditional();
 
         if Self::match_type(self, &[TokenType::EQUAL]) {
             Self::advance(self);
@@ -545,10 +1449,46 @@ impl AST {
                         value: Box::new(value),
                     };
                 }
+                Expression::Index { object, index, .. } => {
+                    return Expression::IndexAssignment {
+                        object,
+                        index,
+                        value: Box::new(value),
+                    };
+                }
 
                 _ => {
-                    eprintln!("Invalid assignment target.");
-                    exit(65);
+                    Self::syntax_error(self, "Invalid assignment target.".to_string());
+                    return expr;
+                }
+            }
+        }
+
+        let compound_targets = [
+            TokenType::PLUS_EQUAL,
+            TokenType::MINUS_EQUAL,
+            TokenType::STAR_EQUAL,
+            TokenType::SLASH_EQUAL,
+            TokenType::PERCENT_EQUAL,
+        ];
+        if Self::match_type(self, &compound_targets) {
+            let operator = Self::peek(self);
+            Self::advance(self);
+
+            let value = Self::expression(self);
+
+            match expr {
+                Expression::Variable { .. } | Expression::Index { .. } => {
+                    return Expression::CompoundAssignment {
+                        target: Box::new(expr),
+                        operator,
+                        value: Box::new(value),
+                    };
+                }
+
+                _ => {
+                    Self::syntax_error(self, "Invalid assignment target.".to_string());
+                    return expr;
                 }
             }
         }
@@ -560,9 +1500,23 @@ impl AST {
         return Self::assignment(self);
     }
 
+    // Parses the tokens as exactly one expression without ending the process on
+    // a syntax error. Used for expressions typed into the debugger.
+    pub fn parse_expression(&mut self) -> Result<Expression, String> {
+        self.recover = true;
+        let expr = Self::expression(self);
+        if !Self::is_at_end(self) {
+            Self::syntax_error(self, "Expect expression.".to_string());
+        }
+        match self.error.take() {
+            Some(message) => Err(message),
+            None => Ok(expr),
+        }
+    }
+
     pub fn parse_tree(&mut self, debug: bool) {
         while !Self::is_at_end(&self) {
-            let expr = Self::equality(self);
+            let expr = Self::conditional(self);
             self.exprs.push(expr);
         }
 
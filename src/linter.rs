@@ -0,0 +1,466 @@
+use std::{collections::HashSet, fs};
+
+use crate::{
+    checker::Type,
+    expr::Expression,
+    pattern::Pattern,
+    printer::{json_string, span_to_json},
+    statement::Statement,
+    token::{Span, Token, TokenType},
+};
+
+pub const RULES: [&str; 6] = [
+    "unused-variable",
+    "shadowed-declaration",
+    "unreachable-code",
+    "self-assignment",
+    "distinct-type-comparison",
+    "empty-block",
+];
+
+// Which rules are turned off. Read from a file of `rule = on|off` lines, where
+// `#` starts a comment.
+pub struct LintConfig {
+    disabled: HashSet<String>,
+}
+
+impl LintConfig {
+    pub fn new() -> LintConfig {
+        LintConfig {
+            disabled: HashSet::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<LintConfig, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|_| format!("Failed to read lint config {}", path))?;
+        let mut config = LintConfig::new();
+
+        for (index, raw) in contents.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((rule, setting)) = line.split_once('=') else {
+                return Err(format!("[line {}] Expect 'rule = on|off'.", index + 1));
+            };
+            let (rule, setting) = (rule.trim(), setting.trim());
+            if !RULES.contains(&rule) {
+                return Err(format!("[line {}] Unknown lint rule '{}'.", index + 1, rule));
+            }
+            match setting {
+                "on" => config.disabled.remove(rule),
+                "off" => config.disabled.insert(rule.to_string()),
+                _ => return Err(format!("[line {}] Expect 'on' or 'off'.", index + 1)),
+            };
+        }
+
+        return Ok(config);
+    }
+}
+
+pub struct Finding {
+    pub rule: &'static str,
+    pub message: String,
+    pub span: Span,
+}
+
+struct Declared {
+    name: Token,
+    used: bool,
+    // The type every value of the variable has so far, `Any` when it is not
+    // known. An annotated variable keeps its declared type.
+    known: Type,
+    annotated: bool,
+}
+
+struct Linter<'a> {
+    config: &'a LintConfig,
+    // Variables declared in each scope, innermost last. The global scope is first.
+    scopes: Vec<Vec<Declared>>,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, rule: &'static str, message: String, span: Span) {
+        if self.config.disabled.contains(rule) {
+            return;
+        }
+        self.findings.push(Finding {
+            rule,
+            message,
+            span,
+        });
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn end_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap_or_default();
+        for declared in scope {
+            if !declared.used {
+                self.report(
+                    "unused-variable",
+                    format!("Local variable '{}' is never read.", declared.name.lexeme),
+                    declared.name.span(),
+                );
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &Token, known: Type, annotated: bool) {
+        let depth = self.scopes.len() - 1;
+        let shadowed = self.scopes[..depth]
+            .iter()
+            .any(|scope| scope.iter().any(|declared| declared.name.lexeme == name.lexeme));
+        if shadowed {
+            self.report(
+                "shadowed-declaration",
+                format!("Declaration of '{}' shadows an outer variable.", name.lexeme),
+                name.span(),
+            );
+        }
+
+        // Globals are never reported as unused, another module may import them.
+        let used = depth == 0;
+        self.scopes[depth].push(Declared {
+            name: name.clone(),
+            used,
+            known,
+            annotated,
+        });
+    }
+
+    // Catch variables and match bindings are not declarations, so they are
+    // never reported as unused or shadowing.
+    fn bind(&mut self, name: &Token) {
+        self.scopes.last_mut().unwrap().push(Declared {
+            name: name.clone(),
+            used: true,
+            known: Type::Any,
+            annotated: false,
+        });
+    }
+
+    fn bind_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Binding { name } => self.bind(name),
+            Pattern::List { elements, .. } => {
+                for element in elements {
+                    self.bind_pattern(element);
+                }
+            }
+            Pattern::Alternatives { patterns } => {
+                for alternative in patterns {
+                    self.bind_pattern(alternative);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn lookup(&mut self, name: &str) -> Option<&mut Declared> {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(declared) = scope.iter_mut().rev().find(|d| d.name.lexeme == name) {
+                return Some(declared);
+            }
+        }
+        return None;
+    }
+
+    fn mark_used(&mut self, name: &str) {
+        if let Some(declared) = self.lookup(name) {
+            declared.used = true;
+        }
+    }
+
+    // Assigning a value of another type, or of an unknown one, forgets the
+    // variable's type. The assignment may sit in a match arm or catch block
+    // that does not always run.
+    fn assigned(&mut self, name: &str, value: Type) {
+        if let Some(declared) = self.lookup(name) {
+            if !declared.annotated && declared.known != value {
+                declared.known = Type::Any;
+            }
+        }
+    }
+
+    // The type an expression is sure to evaluate to, as far as literals and
+    // variables of known type tell.
+    fn known_type(&mut self, expr: &Expression) -> Type {
+        match expr {
+            Expression::Literal { value, .. } => Type::of(value),
+            Expression::ListLiteral { .. } => Type::List,
+            Expression::MapLiteral { .. } => Type::Map,
+            Expression::Grouping { expr, .. } => self.known_type(expr),
+            Expression::Variable { variable } => match self.lookup(&variable.lexeme) {
+                Some(declared) => declared.known.clone(),
+                None => Type::Any,
+            },
+            Expression::Unary { operator, .. } => match operator.token_type {
+                TokenType::BANG => Type::Bool,
+                _ => Type::Number,
+            },
+            Expression::Binary {
+                operator,
+                left_expr,
+                right_expr,
+            } => match operator.token_type {
+                TokenType::EQUAL_EQUAL
+                | TokenType::BANG_EQUAL
+                | TokenType::LESS
+                | TokenType::LESS_EQUAL
+                | TokenType::GREATER
+                | TokenType::GREATER_EQUAL => Type::Bool,
+                // Both sides of `+` have the same type, or it fails at runtime.
+                TokenType::PLUS => {
+                    let left = self.known_type(left_expr);
+                    let right = self.known_type(right_expr);
+                    if left == right {
+                        return left;
+                    }
+                    return Type::Any;
+                }
+                _ => Type::Number,
+            },
+            _ => Type::Any,
+        }
+    }
+
+    fn lint_stats(&mut self, stats: &[Statement]) {
+        let mut thrown = false;
+        let mut reported = false;
+        for stat in stats {
+            // Only the first unreachable statement of a block is reported.
+            if thrown && !reported {
+                self.report(
+                    "unreachable-code",
+                    "Unreachable code after 'throw'.".to_string(),
+                    stat.span(),
+                );
+                reported = true;
+            }
+            self.lint_stat(stat);
+            thrown = thrown || matches!(stat, Statement::ThrowStatement { .. });
+        }
+    }
+
+    fn lint_stat(&mut self, stat: &Statement) {
+        match stat {
+            Statement::PrintStatement { expr, .. }
+            | Statement::ExprStatement { expr, .. }
+            | Statement::ThrowStatement { expr, .. } => self.lint_expr(expr),
+            Statement::DeclStatement {
+                name,
+                value,
+                annotation,
+                ..
+            } => {
+                if let Some(expr) = value {
+                    self.lint_expr(expr);
+                }
+                // `any` and unknown type names say nothing about the value.
+                let annotated = annotation
+                    .as_ref()
+                    .and_then(|annotation| Type::from_name(&annotation.lexeme))
+                    .filter(|annotated| *annotated != Type::Any);
+                let known = match (&annotated, value) {
+                    (Some(annotated), _) => annotated.clone(),
+                    (None, Some(expr)) => self.known_type(expr),
+                    (None, None) => Type::Nil,
+                };
+                self.declare(name, known, annotated.is_some());
+            }
+            Statement::BlockStatement { stats, span } => {
+                if stats.is_empty() {
+                    self.report("empty-block", "Empty block.".to_string(), *span);
+                }
+                self.begin_scope();
+                self.lint_stats(stats);
+                self.end_scope();
+            }
+            Statement::TryStatement {
+                body,
+                handler,
+                finally,
+                ..
+            } => {
+                self.lint_stat(body);
+                if let Some((name, catch_body)) = handler {
+                    self.begin_scope();
+                    self.bind(name);
+                    self.lint_stat(catch_body);
+                    self.end_scope();
+                }
+                if let Some(finally_body) = finally {
+                    self.lint_stat(finally_body);
+                }
+            }
+            Statement::MatchStatement { subject, arms, .. } => {
+                self.lint_expr(subject);
+                for (pattern, body) in arms {
+                    self.begin_scope();
+                    self.bind_pattern(pattern);
+                    self.lint_stat(body);
+                    self.end_scope();
+                }
+            }
+            Statement::ImportStatement { .. } => {}
+        }
+    }
+
+    fn lint_expr(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Literal { .. } => {}
+            Expression::Variable { variable } => self.mark_used(&variable.lexeme),
+            Expression::Grouping { expr, .. } | Expression::Unary { expr, .. } => {
+                self.lint_expr(expr)
+            }
+            Expression::Binary {
+                operator,
+                left_expr,
+                right_expr,
+            } => {
+                self.check_comparison(operator, left_expr, right_expr, expr.span());
+                self.lint_expr(left_expr);
+                self.lint_expr(right_expr);
+            }
+            Expression::Assignment { name, value } => {
+                if let Expression::Variable { variable } = &**value {
+                    if variable.lexeme == name.lexeme {
+                        self.report(
+                            "self-assignment",
+                            format!("'{}' is assigned to itself.", name.lexeme),
+                            expr.span(),
+                        );
+                    }
+                }
+                self.lint_expr(value);
+                let assigned = self.known_type(value);
+                self.assigned(&name.lexeme, assigned);
+            }
+            Expression::CompoundAssignment {
+                target,
+                operator,
+                value,
+            } => {
+                self.lint_expr(target);
+                self.lint_expr(value);
+                if let Expression::Variable { variable } = &**target {
+                    let current = self.known_type(target);
+                    let operand = self.known_type(value);
+                    let assigned = match operator.token_type {
+                        TokenType::PLUS_EQUAL if current == operand => current,
+                        TokenType::PLUS_EQUAL => Type::Any,
+                        _ => Type::Number,
+                    };
+                    self.assigned(&variable.lexeme, assigned);
+                }
+            }
+            Expression::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.lint_expr(condition);
+                self.lint_expr(then_branch);
+                self.lint_expr(else_branch);
+            }
+            Expression::ListLiteral { elements, .. } => {
+                for element in elements {
+                    self.lint_expr(element);
+                }
+            }
+            Expression::MapLiteral { entries, .. } => {
+                for (key, value) in entries {
+                    self.lint_expr(key);
+                    self.lint_expr(value);
+                }
+            }
+            Expression::Index { object, index, .. } => {
+                self.lint_expr(object);
+                self.lint_expr(index);
+            }
+            Expression::IndexAssignment {
+                object,
+                index,
+                value,
+            } => {
+                self.lint_expr(object);
+                self.lint_expr(index);
+                self.lint_expr(value);
+            }
+            Expression::MethodCall {
+                object, arguments, ..
+            } => {
+                self.lint_expr(object);
+                for argument in arguments {
+                    self.lint_expr(argument);
+                }
+            }
+        }
+    }
+
+    // `==` between values of different types, such as `count == nil` when
+    // `count` only ever held numbers, can never be true.
+    fn check_comparison(&mut self, operator: &Token, left: &Expression, right: &Expression, span: Span) {
+        let outcome = match operator.token_type {
+            TokenType::EQUAL_EQUAL => "false",
+            TokenType::BANG_EQUAL => "true",
+            _ => return,
+        };
+        let left_type = self.known_type(left);
+        let right_type = self.known_type(right);
+        if !left_type.accepts(&right_type) {
+            self.report(
+                "distinct-type-comparison",
+                format!(
+                    "Comparing {} with {} using '{}' is always {}.",
+                    left_type, right_type, operator.lexeme, outcome
+                ),
+                span,
+            );
+        }
+    }
+}
+
+pub fn lint_program(stats: &[Statement], config: &LintConfig) -> Vec<Finding> {
+    let mut linter = Linter {
+        config,
+        scopes: vec![Vec::new()],
+        findings: Vec::new(),
+    };
+    linter.lint_stats(stats);
+    linter.end_scope();
+
+    let mut findings = linter.findings;
+    findings.sort_by_key(|finding| (finding.span.line, finding.span.column));
+    return findings;
+}
+
+pub fn print_findings(findings: &[Finding]) {
+    for finding in findings {
+        println!(
+            "[line {}:{}] {}: {}",
+            finding.span.line, finding.span.column, finding.rule, finding.message
+        );
+    }
+}
+
+pub fn print_findings_json(findings: &[Finding]) {
+    let items: Vec<String> = findings
+        .iter()
+        .map(|finding| {
+            format!(
+                "{{\"rule\":{},\"message\":{},\"span\":{}}}",
+                json_string(finding.rule),
+                json_string(&finding.message),
+                span_to_json(&finding.span)
+            )
+        })
+        .collect();
+    println!("{{\"findings\":[{}]}}", items.join(","));
+}
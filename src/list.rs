@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    expr::{evaluation_error, Literal, RuntimeError},
+    map::format_map,
+    token::Token,
+};
+
+// Lists are shared by reference, so `var b = a; b.push(1);` is visible through `a`.
+pub type ListRef = Arc<Mutex<Vec<Literal>>>;
+
+pub fn new_list(items: Vec<Literal>) -> Literal {
+    return Literal::List(Arc::new(Mutex::new(items)));
+}
+
+// Converts `index` to a position in a list of length `len`. The end of the list
+// itself is only a valid position when inserting or slicing.
+fn to_position(index: &Literal, len: usize, allow_end: bool) -> Result<usize, RuntimeError> {
+    let Literal::Int(n) = index else {
+        return evaluation_error("List index must be an integer.");
+    };
+
+    let in_range = if allow_end { *n <= len as i64 } else { *n < len as i64 };
+    if *n < 0 || !in_range {
+        return evaluation_error("List index out of range.");
+    }
+    return Ok(*n as usize);
+}
+
+pub fn check_arity(arguments: &[Literal], expected: usize) -> Result<(), RuntimeError> {
+    if arguments.len() != expected {
+        return evaluation_error(&format!(
+            "Expected {} arguments but got {}.",
+            expected,
+            arguments.len()
+        ));
+    }
+    return Ok(());
+}
+
+pub fn get_index(list: &ListRef, index: &Literal) -> Result<Literal, RuntimeError> {
+    let items = list.lock().unwrap();
+    let position = to_position(index, items.len(), false)?;
+    return Ok(items[position].clone());
+}
+
+pub fn set_index(list: &ListRef, index: &Literal, value: Literal) -> Result<Literal, RuntimeError> {
+    let mut items = list.lock().unwrap();
+    let position = to_position(index, items.len(), false)?;
+    items[position] = value.clone();
+    return Ok(value);
+}
+
+pub fn call_method(
+    list: &ListRef,
+    name: &Token,
+    arguments: Vec<Literal>,
+) -> Result<Literal, RuntimeError> {
+    let mut items = list.lock().unwrap();
+
+    match name.lexeme.as_str() {
+        "push" => {
+            check_arity(&arguments, 1)?;
+            items.push(arguments[0].clone());
+            return Ok(Literal::Nil);
+        }
+        "pop" => {
+            check_arity(&arguments, 0)?;
+            match items.pop() {
+                Some(value) => Ok(value),
+                None => evaluation_error("Cannot pop from an empty list."),
+            }
+        }
+        "len" => {
+            check_arity(&arguments, 0)?;
+            return Ok(Literal::Int(items.len() as i64));
+        }
+        "insert" => {
+            check_arity(&arguments, 2)?;
+            let position = to_position(&arguments[0], items.len(), true)?;
+            items.insert(position, arguments[1].clone());
+            return Ok(Literal::Nil);
+        }
+        "remove" => {
+            check_arity(&arguments, 1)?;
+            let position = to_position(&arguments[0], items.len(), false)?;
+            return Ok(items.remove(position));
+        }
+        "slice" => {
+            check_arity(&arguments, 2)?;
+            let start = to_position(&arguments[0], items.len(), true)?;
+            let end = to_position(&arguments[1], items.len(), true)?;
+            if start > end {
+                return evaluation_error("List index out of range.");
+            }
+            return Ok(new_list(items[start..end].to_vec()));
+        }
+        _ => evaluation_error(&format!("Undefined property '{}'.", name.lexeme)),
+    }
+}
+
+// Formats a value nested inside a list or map. `seen` holds the containers
+// currently being printed, so a container that contains itself prints as `[...]`
+// or `{...}`.
+pub fn format_element(item: &Literal, seen: &mut Vec<usize>) -> String {
+    match item {
+        Literal::String(s) => format!("\"{}\"", s),
+        Literal::List(inner) => format_list(inner, seen),
+        Literal::Map(inner) => format_map(inner, seen),
+        _ => item.to_lox_string(),
+    }
+}
+
+pub fn format_list(list: &ListRef, seen: &mut Vec<usize>) -> String {
+    let ptr = Arc::as_ptr(list) as usize;
+    if seen.contains(&ptr) {
+        return "[...]".to_string();
+    }
+    seen.push(ptr);
+
+    let items = list.lock().unwrap();
+    let parts: Vec<String> = items.iter().map(|item| format_element(item, seen)).collect();
+
+    seen.pop();
+    return format!("[{}]", parts.join(", "));
+}
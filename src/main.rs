@@ -1,18 +1,28 @@
 use std::{env, fs};
+use std::path::Path;
 use std::io::{self, Write};
 use std::process::exit;
 
-use expr::Literal;
 use statement::SST;
 
 use crate::expr::AST;
+use crate::printer::Format;
 use crate::tokenizer::Tokenizer;
 
+pub mod checker;
 pub mod expr;
+pub mod linter;
+pub mod list;
+pub mod map;
+pub mod module;
+pub mod optimizer;
+pub mod pattern;
 pub mod statement;
 pub mod token;
 pub mod tokenizer;
 pub mod environment;
+pub mod debugger;
+pub mod printer;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -22,18 +32,54 @@ fn main() {
     }
 
     let command = &args[1];
-    let filename = &args[2];
+    // Options start with `--`, apart from the optimization levels.
+    let is_option = |arg: &&String| arg.starts_with("--") || ["-O0", "-O1"].contains(&arg.as_str());
+    let options: Vec<&String> = args[2..].iter().filter(is_option).collect();
+    let filename = match args[2..].iter().find(|arg| !is_option(arg)) {
+        Some(filename) => filename,
+        None => {
+            writeln!(io::stderr(), "Usage: {} {} <filename>", args[0], command).unwrap();
+            return;
+        }
+    };
+    let format = options
+        .iter()
+        .find_map(|option| option.strip_prefix("--format="));
+
+    if options.iter().any(|option| option.as_str() == "--lenient-coercion") {
+        expr::set_lenient_coercion(true);
+    }
+
+    for option in &options {
+        match option.as_str() {
+            "-O0" => optimizer::set_optimize(false),
+            "-O1" => optimizer::set_optimize(true),
+            _ => {}
+        }
+    }
 
     let file_contents = fs::read_to_string(filename).unwrap_or_else(|_| {
         writeln!(io::stderr(), "Failed to read file {}", filename).unwrap();
         String::new()
     });
+    module::set_entry(filename);
 
     match command.as_str() {
         "tokenize" => {
             writeln!(io::stderr(), "Logs from your program will appear here!").unwrap();
 
-            if !file_contents.is_empty() {
+            if let Some(name) = format {
+                if name != "json" {
+                    writeln!(io::stderr(), "Unknown format: {}", name).unwrap();
+                    exit(64);
+                }
+
+                let mut tokenizer = Tokenizer::new();
+                let result = tokenizer.scan(file_contents.clone());
+                tokenizer.print_tokens_json(&file_contents);
+
+                exit(result);
+            } else if !file_contents.is_empty() {
                 let mut tokenizer = Tokenizer::new();
 
                 let result = tokenizer.scan(file_contents.clone());
@@ -45,7 +91,23 @@ fn main() {
             }
         }
         "parse" => {
-            if !file_contents.is_empty() {
+            if let Some(name) = format {
+                let Some(format) = Format::from_name(name) else {
+                    writeln!(io::stderr(), "Unknown format: {}", name).unwrap();
+                    exit(64);
+                };
+
+                let mut tokenizer = Tokenizer::new();
+                let result = tokenizer.scan(file_contents.clone());
+
+                if result != 0 {
+                    exit(result);
+                }
+
+                let mut sst = SST::new(AST::new(tokenizer.get_tokens()));
+                sst.parse_program();
+                printer::print_program(&sst.export_stats(), format);
+            } else if !file_contents.is_empty() {
                 let mut tokenizer = Tokenizer::new();
                 let result = tokenizer.scan(file_contents.clone());
                 let tokens = tokenizer.get_tokens();
@@ -76,26 +138,78 @@ fn main() {
                 let exprs = ast.export_exprs();
 
                 for expr in exprs {
-                    let val = expr.accept();
-                    match val {
-                        Literal::String(s) => println!("{}", s),
-                        Literal::Nil => println!("nil"),
-                        Literal::Bool(s) => println!("{}", s),
-                        Literal::Number(n) => {
-                            let _ = if n.fract() == 0.0 {
-                                // If there is no fractional part, show one decimal place
-                                println!("{:.0}", n)
-                            } else {
-                                // Otherwise, show the full precision
-                                println!("{}", n)
-                            };
-                        }
+                    match expr.accept() {
+                        Ok(val) => val.print(),
+                        Err(error) => error.report(),
                     }
                 }
             } else {
                 println!("EOF  null"); // Placeholder, remove this line when implementing the scanner
             }
         }
+        "check" => {
+            let mut tokenizer = Tokenizer::new();
+            let result = tokenizer.scan(file_contents.clone());
+
+            if result != 0 {
+                exit(result);
+            }
+
+            let mut sst = SST::new(AST::new(tokenizer.get_tokens()));
+            sst.parse_program();
+            let errors = checker::check_program(&sst.export_stats());
+            for error in &errors {
+                writeln!(io::stderr(), "{}", error).unwrap();
+            }
+            if !errors.is_empty() {
+                exit(65);
+            }
+        }
+        "lint" => {
+            // `--config=path` picks the rule settings, otherwise a `.loxlint`
+            // next to the script is used when there is one.
+            let config_path = match options
+                .iter()
+                .find_map(|option| option.strip_prefix("--config="))
+            {
+                Some(path) => Some(path.to_string()),
+                None => {
+                    let default = Path::new(filename).with_file_name(".loxlint");
+                    default
+                        .exists()
+                        .then(|| default.to_string_lossy().to_string())
+                }
+            };
+            let config = match config_path {
+                Some(path) => linter::LintConfig::load(&path).unwrap_or_else(|message| {
+                    writeln!(io::stderr(), "{}", message).unwrap();
+                    exit(64);
+                }),
+                None => linter::LintConfig::new(),
+            };
+
+            let mut tokenizer = Tokenizer::new();
+            let result = tokenizer.scan(file_contents.clone());
+
+            if result != 0 {
+                exit(result);
+            }
+
+            let mut sst = SST::new(AST::new(tokenizer.get_tokens()));
+            sst.parse_program();
+            let findings = linter::lint_program(&sst.export_stats(), &config);
+            match format {
+                None => linter::print_findings(&findings),
+                Some("json") => linter::print_findings_json(&findings),
+                Some(name) => {
+                    writeln!(io::stderr(), "Unknown format: {}", name).unwrap();
+                    exit(64);
+                }
+            }
+            if !findings.is_empty() {
+                exit(1);
+            }
+        }
         "run" => {
             if !file_contents.is_empty() {
                 let mut tokenizer = Tokenizer::new();
@@ -114,6 +228,23 @@ fn main() {
                 println!("EOF  null"); // Placeholder, remove this line when implementing the scanner
             }
         }
+        "debug" => {
+            if !file_contents.is_empty() {
+                let mut tokenizer = Tokenizer::new();
+                let result = tokenizer.scan(file_contents.clone());
+
+                if result != 0 {
+                    exit(result);
+                }
+
+                let tokens = tokenizer.get_tokens();
+
+                debugger::start(&file_contents);
+                let ast = AST::new(tokens);
+                let mut sst = SST::new(ast);
+                sst.parse_tree();
+            }
+        }
         _ => {
             writeln!(io::stderr(), "Unknown command: {}", command).unwrap();
             return;
@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    expr::{evaluation_error, integral_float, Literal, RuntimeError},
+    list::{check_arity, format_element, new_list},
+    token::Token,
+};
+
+// Maps are shared by reference like lists. Entries keep their insertion order,
+// and `index` points every hashed key at its entry.
+pub struct Map {
+    entries: Vec<(Literal, Literal)>,
+    index: HashMap<MapKey, usize>,
+}
+
+pub type MapRef = Arc<Mutex<Map>>;
+
+// The hashable form of a key. Integral numbers hash the same whether they are
+// an int or a float, since `1 == 1.0` in Lox.
+#[derive(PartialEq, Eq, Hash)]
+enum MapKey {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Float(u64),
+    String(String),
+}
+
+fn to_key(key: &Literal) -> Result<MapKey, RuntimeError> {
+    match key {
+        Literal::Nil => Ok(MapKey::Nil),
+        Literal::Bool(b) => Ok(MapKey::Bool(*b)),
+        Literal::Int(n) => Ok(MapKey::Int(*n)),
+        Literal::Number(n) => match integral_float(*n) {
+            Some(int) => Ok(MapKey::Int(int)),
+            None => Ok(MapKey::Float(n.to_bits())),
+        },
+        Literal::String(s) => Ok(MapKey::String(s.clone())),
+        _ => evaluation_error("Map keys must be strings, numbers, booleans or nil."),
+    }
+}
+
+impl Map {
+    fn get(&self, key: &Literal) -> Result<Option<Literal>, RuntimeError> {
+        let position = self.index.get(&to_key(key)?);
+        return Ok(position.map(|position| self.entries[*position].1.clone()));
+    }
+
+    fn insert(&mut self, key: Literal, value: Literal) -> Result<(), RuntimeError> {
+        let hashed = to_key(&key)?;
+        match self.index.get(&hashed) {
+            Some(position) => self.entries[*position].1 = value,
+            None => {
+                self.index.insert(hashed, self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+        return Ok(());
+    }
+
+    fn remove(&mut self, key: &Literal) -> Result<Option<Literal>, RuntimeError> {
+        let Some(position) = self.index.remove(&to_key(key)?) else {
+            return Ok(None);
+        };
+        let (_, value) = self.entries.remove(position);
+        // Every entry after the removed one moved back by one.
+        for later in self.index.values_mut() {
+            if *later > position {
+                *later -= 1;
+            }
+        }
+        return Ok(Some(value));
+    }
+}
+
+pub fn new_map(entries: Vec<(Literal, Literal)>) -> Result<Literal, RuntimeError> {
+    let mut map = Map {
+        entries: Vec::new(),
+        index: HashMap::new(),
+    };
+    for (key, value) in entries {
+        map.insert(key, value)?;
+    }
+    return Ok(Literal::Map(Arc::new(Mutex::new(map))));
+}
+
+fn missing_key<T>(key: &Literal) -> Result<T, RuntimeError> {
+    return evaluation_error(&format!(
+        "Undefined key {}.",
+        format_element(key, &mut Vec::new())
+    ));
+}
+
+pub fn get_entry(map: &MapRef, key: &Literal) -> Result<Literal, RuntimeError> {
+    match map.lock().unwrap().get(key)? {
+        Some(value) => Ok(value),
+        None => missing_key(key),
+    }
+}
+
+pub fn set_entry(map: &MapRef, key: &Literal, value: Literal) -> Result<Literal, RuntimeError> {
+    map.lock().unwrap().insert(key.clone(), value.clone())?;
+    return Ok(value);
+}
+
+pub fn call_method(
+    map: &MapRef,
+    name: &Token,
+    arguments: Vec<Literal>,
+) -> Result<Literal, RuntimeError> {
+    let mut map = map.lock().unwrap();
+
+    match name.lexeme.as_str() {
+        "keys" => {
+            check_arity(&arguments, 0)?;
+            return Ok(new_list(map.entries.iter().map(|(key, _)| key.clone()).collect()));
+        }
+        "values" => {
+            check_arity(&arguments, 0)?;
+            return Ok(new_list(map.entries.iter().map(|(_, value)| value.clone()).collect()));
+        }
+        "has" => {
+            check_arity(&arguments, 1)?;
+            return Ok(Literal::Bool(map.get(&arguments[0])?.is_some()));
+        }
+        "remove" => {
+            check_arity(&arguments, 1)?;
+            match map.remove(&arguments[0])? {
+                Some(value) => Ok(value),
+                None => missing_key(&arguments[0]),
+            }
+        }
+        "len" => {
+            check_arity(&arguments, 0)?;
+            return Ok(Literal::Int(map.entries.len() as i64));
+        }
+        _ => evaluation_error(&format!("Undefined property '{}'.", name.lexeme)),
+    }
+}
+
+pub fn format_map(map: &MapRef, seen: &mut Vec<usize>) -> String {
+    let ptr = Arc::as_ptr(map) as usize;
+    if seen.contains(&ptr) {
+        return "{...}".to_string();
+    }
+    seen.push(ptr);
+
+    let map = map.lock().unwrap();
+    let parts: Vec<String> = map
+        .entries
+        .iter()
+        .map(|(key, value)| format!("{}: {}", format_element(key, seen), format_element(value, seen)))
+        .collect();
+
+    seen.pop();
+    return format!("{{{}}}", parts.join(", "));
+}
+
+// Used by the printer, which has no access to the private entries.
+pub fn entries(map: &MapRef) -> Vec<(Literal, Literal)> {
+    return map.lock().unwrap().entries.clone();
+}
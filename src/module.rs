@@ -0,0 +1,129 @@
+use lazy_static::lazy_static;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::{
+    environment::with_module_env,
+    expr::{evaluation_error, Literal, RuntimeError, AST},
+    map::new_map,
+    statement::SST,
+    tokenizer::Tokenizer,
+};
+
+// A module that finished running: its top-level variables as a map, and the
+// names among them declared `const`.
+#[derive(Clone)]
+pub struct Module {
+    pub value: Literal,
+    pub constants: HashSet<String>,
+}
+
+struct Modules {
+    // Every module that finished running, keyed by its canonical path.
+    cache: HashMap<PathBuf, Module>,
+    // The files currently being run, outermost first. The entry script is at the bottom.
+    loading: Vec<PathBuf>,
+}
+
+lazy_static! {
+    static ref MODULES: Mutex<Modules> = Mutex::new(Modules {
+        cache: HashMap::new(),
+        loading: Vec::new(),
+    });
+}
+
+fn display_path(path: &Path) -> String {
+    return path.display().to_string();
+}
+
+// Records the script given on the command line, so its imports resolve next to it.
+pub fn set_entry(filename: &str) {
+    let path = fs::canonicalize(filename).unwrap_or_else(|_| PathBuf::from(filename));
+    MODULES.lock().unwrap().loading.push(path);
+}
+
+fn resolve(path: &str) -> Result<PathBuf, RuntimeError> {
+    let base = match MODULES.lock().unwrap().loading.last() {
+        Some(importer) => importer.parent().map(Path::to_path_buf).unwrap_or_default(),
+        None => PathBuf::new(),
+    };
+    match fs::canonicalize(base.join(path)) {
+        Ok(resolved) => Ok(resolved),
+        Err(_) => evaluation_error(&format!("Could not find module '{}'.", path)),
+    }
+}
+
+// The whole module is parsed before any of it runs, so a syntax error is a
+// catchable error at the import rather than a half-run module.
+fn run_module(path: &Path) -> Result<(), RuntimeError> {
+    let name = display_path(path);
+    let Ok(source) = fs::read_to_string(path) else {
+        return evaluation_error(&format!("Could not read module '{}'.", name));
+    };
+
+    let mut tokenizer = Tokenizer::new();
+    tokenizer.set_quiet(true);
+    if tokenizer.scan(source) != 0 {
+        let error = &tokenizer.get_errors()[0];
+        return evaluation_error(&format!(
+            "Could not parse module '{}': [line {}] Error: {}",
+            name, error.line, error.message
+        ));
+    }
+
+    let mut sst = SST::new(AST::new(tokenizer.get_tokens()));
+    if let Err(message) = sst.try_parse_program() {
+        return evaluation_error(&format!("Could not parse module '{}': {}", name, message));
+    }
+    return sst.run_parsed().map_err(|error| error.in_module(&name));
+}
+
+// Runs the module at `path` the first time it is imported and returns its
+// top-level variables as a map. Later imports get the same map back.
+pub fn load(path: &str) -> Result<Module, RuntimeError> {
+    let resolved = resolve(path)?;
+
+    {
+        let modules = MODULES.lock().unwrap();
+        if let Some(module) = modules.cache.get(&resolved) {
+            return Ok(module.clone());
+        }
+        if let Some(start) = modules.loading.iter().position(|file| *file == resolved) {
+            let mut cycle: Vec<String> = modules.loading[start..]
+                .iter()
+                .map(|file| display_path(file))
+                .collect();
+            cycle.push(display_path(&resolved));
+            return evaluation_error(&format!("Import cycle: {}.", cycle.join(" -> ")));
+        }
+    }
+
+    MODULES.lock().unwrap().loading.push(resolved.clone());
+    let result = with_module_env(|| run_module(&resolved));
+    MODULES.lock().unwrap().loading.pop();
+
+    let vars = result?;
+    let constants = vars
+        .iter()
+        .filter(|(_, _, constant)| *constant)
+        .map(|(name, _, _)| name.clone())
+        .collect();
+    let entries = vars
+        .into_iter()
+        .map(|(name, value, _)| (Literal::String(name), value))
+        .collect();
+    let module = Module {
+        value: new_map(entries)?,
+        constants,
+    };
+    MODULES
+        .lock()
+        .unwrap()
+        .cache
+        .insert(resolved, module.clone());
+    return Ok(module);
+}
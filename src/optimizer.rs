@@ -0,0 +1,238 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{expr::Expression, statement::Statement};
+
+// `-O1`, the default, folds constant expressions and drops code that can never
+// run before a statement executes. `-O0` runs the tree exactly as parsed.
+static OPTIMIZE: AtomicBool = AtomicBool::new(true);
+
+pub fn set_optimize(enabled: bool) {
+    OPTIMIZE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    return OPTIMIZE.load(Ordering::Relaxed);
+}
+
+pub fn optimize_stat(stat: Statement) -> Statement {
+    match stat {
+        Statement::PrintStatement { expr, span } => Statement::PrintStatement {
+            expr: optimize_expr(expr),
+            span,
+        },
+        Statement::ExprStatement { expr, span } => Statement::ExprStatement {
+            expr: optimize_expr(expr),
+            span,
+        },
+        Statement::ThrowStatement { expr, span } => Statement::ThrowStatement {
+            expr: optimize_expr(expr),
+            span,
+        },
+        Statement::DeclStatement {
+            name,
+            value,
+            constant,
+            annotation,
+            span,
+        } => Statement::DeclStatement {
+            name,
+            value: value.map(optimize_expr),
+            constant,
+            annotation,
+            span,
+        },
+        Statement::BlockStatement { stats, span } => Statement::BlockStatement {
+            stats: optimize_stats(stats),
+            span,
+        },
+        Statement::TryStatement {
+            body,
+            handler,
+            finally,
+            span,
+        } => Statement::TryStatement {
+            body: Box::new(optimize_stat(*body)),
+            handler: handler.map(|(name, body)| (name, Box::new(optimize_stat(*body)))),
+            finally: finally.map(|body| Box::new(optimize_stat(*body))),
+            span,
+        },
+        Statement::MatchStatement {
+            subject,
+            arms,
+            span,
+        } => {
+            let subject = optimize_expr(subject);
+            let mut arms: Vec<_> = arms
+                .into_iter()
+                .map(|(pattern, body)| (pattern, optimize_stat(body)))
+                .collect();
+            // With a constant subject only the first matching arm can ever run.
+            // It stays inside the match so its bindings are still defined.
+            if let Expression::Literal { value, .. } = &subject {
+                let chosen = arms
+                    .iter()
+                    .position(|(pattern, _)| pattern.matches(value, &mut Vec::new()));
+                arms = match chosen {
+                    Some(position) => vec![arms.swap_remove(position)],
+                    None => Vec::new(),
+                };
+            }
+            Statement::MatchStatement {
+                subject,
+                arms,
+                span,
+            }
+        }
+        Statement::ImportStatement { .. } => stat,
+    }
+}
+
+// Nothing after a `throw` in the same block can run.
+fn optimize_stats(stats: Vec<Statement>) -> Vec<Statement> {
+    let mut optimized = Vec::new();
+    for stat in stats {
+        let stop = matches!(stat, Statement::ThrowStatement { .. });
+        optimized.push(optimize_stat(stat));
+        if stop {
+            break;
+        }
+    }
+    return optimized;
+}
+
+fn is_constant(expr: &Expression) -> bool {
+    return matches!(expr, Expression::Literal { .. });
+}
+
+// Evaluates an expression whose operands are all literals. One that fails at
+// runtime, like `-"x"`, is left alone so the error still happens when it runs.
+fn fold(expr: Expression) -> Expression {
+    match expr.accept() {
+        Ok(value) => Expression::Literal {
+            value,
+            span: expr.span(),
+        },
+        Err(_) => expr,
+    }
+}
+
+pub fn optimize_expr(expr: Expression) -> Expression {
+    match expr {
+        Expression::Literal { .. } | Expression::Variable { .. } => expr,
+        Expression::Grouping { expr, span } => {
+            let inner = optimize_expr(*expr);
+            if is_constant(&inner) {
+                return inner;
+            }
+            return Expression::Grouping {
+                expr: Box::new(inner),
+                span,
+            };
+        }
+        Expression::Unary { operator, expr } => {
+            let operand = optimize_expr(*expr);
+            let folded = is_constant(&operand);
+            let unary = Expression::Unary {
+                operator,
+                expr: Box::new(operand),
+            };
+            if folded {
+                return fold(unary);
+            }
+            return unary;
+        }
+        Expression::Binary {
+            operator,
+            left_expr,
+            right_expr,
+        } => {
+            let left = optimize_expr(*left_expr);
+            let right = optimize_expr(*right_expr);
+            let folded = is_constant(&left) && is_constant(&right);
+            let binary = Expression::Binary {
+                operator,
+                left_expr: Box::new(left),
+                right_expr: Box::new(right),
+            };
+            if folded {
+                return fold(binary);
+            }
+            return binary;
+        }
+        Expression::Conditional {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            let condition = optimize_expr(*condition);
+            let then_branch = optimize_expr(*then_branch);
+            let else_branch = optimize_expr(*else_branch);
+            if let Expression::Literal { value, .. } = &condition {
+                if let Ok(true) = value.to_bool() {
+                    return then_branch;
+                }
+                return else_branch;
+            }
+            return Expression::Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            };
+        }
+        Expression::Assignment { name, value } => Expression::Assignment {
+            name,
+            value: Box::new(optimize_expr(*value)),
+        },
+        Expression::CompoundAssignment {
+            target,
+            operator,
+            value,
+        } => Expression::CompoundAssignment {
+            target,
+            operator,
+            value: Box::new(optimize_expr(*value)),
+        },
+        // Every evaluation of a list or map literal makes a new value, so these
+        // are never folded themselves.
+        Expression::ListLiteral { elements, span } => Expression::ListLiteral {
+            elements: elements.into_iter().map(optimize_expr).collect(),
+            span,
+        },
+        Expression::MapLiteral { entries, span } => Expression::MapLiteral {
+            entries: entries
+                .into_iter()
+                .map(|(key, value)| (optimize_expr(key), optimize_expr(value)))
+                .collect(),
+            span,
+        },
+        Expression::Index {
+            object,
+            index,
+            bracket,
+        } => Expression::Index {
+            object: Box::new(optimize_expr(*object)),
+            index: Box::new(optimize_expr(*index)),
+            bracket,
+        },
+        Expression::IndexAssignment {
+            object,
+            index,
+            value,
+        } => Expression::IndexAssignment {
+            object: Box::new(optimize_expr(*object)),
+            index: Box::new(optimize_expr(*index)),
+            value: Box::new(optimize_expr(*value)),
+        },
+        Expression::MethodCall {
+            object,
+            name,
+            arguments,
+            paren,
+        } => Expression::MethodCall {
+            object: Box::new(optimize_expr(*object)),
+            name,
+            arguments: arguments.into_iter().map(optimize_expr).collect(),
+            paren,
+        },
+    }
+}
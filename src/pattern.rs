@@ -0,0 +1,94 @@
+use crate::{
+    expr::{format_number_literal, Literal},
+    token::Token,
+};
+
+// pattern     → alternative ( "|" alternative )* ;
+// alternative → literal | "_" | IDENTIFIER | "[" ( pattern ( "," pattern )* )? "]" ;
+#[derive(Clone)]
+pub enum Pattern {
+    Literal { value: Literal, token: Token },
+    Wildcard { token: Token },
+    Binding { name: Token },
+    List { elements: Vec<Pattern>, bracket: Token },
+    Alternatives { patterns: Vec<Pattern> },
+}
+
+impl Pattern {
+    pub fn line(&self) -> i32 {
+        match self {
+            Pattern::Literal { token, .. } => token.line,
+            Pattern::Wildcard { token } => token.line,
+            Pattern::Binding { name } => name.line,
+            Pattern::List { bracket, .. } => bracket.line,
+            Pattern::Alternatives { patterns } => patterns[0].line(),
+        }
+    }
+
+    // A pattern that matches every value, so any arm after it can never run.
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Pattern::Wildcard { .. } | Pattern::Binding { .. } => true,
+            Pattern::Alternatives { patterns } => patterns.iter().any(Pattern::is_irrefutable),
+            _ => false,
+        }
+    }
+
+    // Checks `value` against the pattern, pushing the variables it binds.
+    // `bindings` may hold partial results when the match fails.
+    pub fn matches(&self, value: &Literal, bindings: &mut Vec<(String, Literal)>) -> bool {
+        match self {
+            Pattern::Literal { value: expected, .. } => value == expected,
+            Pattern::Wildcard { .. } => true,
+            Pattern::Binding { name } => {
+                bindings.push((name.lexeme.clone(), value.clone()));
+                return true;
+            }
+            Pattern::List { elements, .. } => {
+                let Literal::List(items) = value else {
+                    return false;
+                };
+                // Copy the items so the lock is not held while matching nested lists.
+                let items = items.lock().unwrap().clone();
+                return items.len() == elements.len()
+                    && elements
+                        .iter()
+                        .zip(items.iter())
+                        .all(|(element, item)| element.matches(item, bindings));
+            }
+            Pattern::Alternatives { patterns } => {
+                for pattern in patterns {
+                    let mut attempt = Vec::new();
+                    if pattern.matches(value, &mut attempt) {
+                        bindings.append(&mut attempt);
+                        return true;
+                    }
+                }
+                return false;
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Literal { value, .. } => match value {
+                Literal::String(s) => f.write_fmt(format_args!("\"{}\"", s)),
+                Literal::Number(n) => f.write_str(&format_number_literal(*n)),
+                Literal::Int(n) => f.write_fmt(format_args!("{}.0", n)),
+                _ => f.write_fmt(format_args!("{}", value)),
+            },
+            Pattern::Wildcard { .. } => f.write_str("_"),
+            Pattern::Binding { name } => f.write_str(&name.lexeme),
+            Pattern::List { elements, .. } => {
+                let parts: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                f.write_fmt(format_args!("[{}]", parts.join(", ")))
+            }
+            Pattern::Alternatives { patterns } => {
+                let parts: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+                f.write_str(&parts.join(" | "))
+            }
+        }
+    }
+}
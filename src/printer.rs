@@ -0,0 +1,600 @@
+use crate::{
+    expr::{format_number_literal, Expression, Literal},
+    map,
+    pattern::Pattern,
+    statement::Statement,
+    token::{Span, Token},
+};
+
+pub enum Format {
+    Sexpr,
+    Json,
+    Tree,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "sexpr" => Some(Format::Sexpr),
+            "json" => Some(Format::Json),
+            "tree" => Some(Format::Tree),
+            _ => None,
+        }
+    }
+}
+
+pub fn print_program(stats: &Vec<Statement>, format: Format) {
+    match format {
+        Format::Sexpr => {
+            for stat in stats {
+                println!("{}", stat_to_sexpr(stat));
+            }
+        }
+        Format::Json => {
+            let items: Vec<String> = stats.iter().map(stat_to_json).collect();
+            println!(
+                "{{\"kind\":\"Program\",\"statements\":[{}]}}",
+                items.join(",")
+            );
+        }
+        Format::Tree => {
+            let mut out = String::from("Program\n");
+            for stat in stats {
+                stat_to_tree(stat, 1, &mut out);
+            }
+            print!("{}", out);
+        }
+    }
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for char in s.chars() {
+        match char {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+pub fn span_to_json(span: &Span) -> String {
+    return format!(
+        "{{\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{}}}",
+        span.line, span.column, span.end_line, span.end_column
+    );
+}
+
+// JSON has no infinity or NaN, so those become null. A decimal literal too
+// large for an f64 parses as infinity.
+pub fn number_to_json(n: f64) -> String {
+    if n.is_finite() {
+        return n.to_string();
+    }
+    return "null".to_string();
+}
+
+fn literal_to_json(lit: &Literal) -> String {
+    match lit {
+        Literal::String(s) => json_string(s),
+        Literal::Nil => "null".to_string(),
+        Literal::Bool(b) => b.to_string(),
+        Literal::Number(n) => number_to_json(*n),
+        Literal::Int(n) => n.to_string(),
+        Literal::List(items) => {
+            let items: Vec<String> = items.lock().unwrap().iter().map(literal_to_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        // JSON objects only allow string keys, so entries become `[key, value]` pairs.
+        Literal::Map(entries) => {
+            let items: Vec<String> = map::entries(entries)
+                .iter()
+                .map(|(key, value)| format!("[{},{}]", literal_to_json(key), literal_to_json(value)))
+                .collect();
+            format!("[{}]", items.join(","))
+        }
+    }
+}
+
+fn token_to_json(token: &Token) -> String {
+    return format!(
+        "{{\"type\":\"{:?}\",\"lexeme\":{},\"span\":{}}}",
+        token.token_type,
+        json_string(&token.lexeme),
+        span_to_json(&token.span())
+    );
+}
+
+fn stat_to_sexpr(stat: &Statement) -> String {
+    match stat {
+        Statement::PrintStatement { expr, .. } => format!("(print {})", expr),
+        Statement::ExprStatement { expr, .. } => format!("(expr {})", expr),
+        Statement::DeclStatement {
+            name,
+            value,
+            constant,
+            annotation,
+            ..
+        } => {
+            let keyword = if *constant { "const" } else { "var" };
+            let target = match annotation {
+                Some(annotation) => format!("{}:{}", name.lexeme, annotation.lexeme),
+                None => name.lexeme.clone(),
+            };
+            match value {
+                Some(expr) => format!("({} {} {})", keyword, target, expr),
+                None => format!("({} {})", keyword, target),
+            }
+        }
+        Statement::BlockStatement { stats, .. } => {
+            let mut out = String::from("(block");
+            for stat in stats {
+                out.push(' ');
+                out.push_str(&stat_to_sexpr(stat));
+            }
+            out.push(')');
+            out
+        }
+        Statement::ThrowStatement { expr, .. } => format!("(throw {})", expr),
+        Statement::TryStatement {
+            body,
+            handler,
+            finally,
+            ..
+        } => {
+            let mut out = format!("(try {}", stat_to_sexpr(body));
+            if let Some((name, catch_body)) = handler {
+                out.push_str(&format!(" (catch {} {})", name.lexeme, stat_to_sexpr(catch_body)));
+            }
+            if let Some(finally_body) = finally {
+                out.push_str(&format!(" (finally {})", stat_to_sexpr(finally_body)));
+            }
+            out.push(')');
+            out
+        }
+        Statement::MatchStatement { subject, arms, .. } => {
+            let mut out = format!("(match {}", subject);
+            for (pattern, body) in arms {
+                out.push_str(&format!(" (arm {} {})", pattern, stat_to_sexpr(body)));
+            }
+            out.push(')');
+            out
+        }
+        Statement::ImportStatement { name, path, .. } => match name {
+            Some(name) => format!("(import {} {})", name.lexeme, path.lexeme),
+            None => format!("(import {})", path.lexeme),
+        },
+    }
+}
+
+fn stat_to_json(stat: &Statement) -> String {
+    let span = span_to_json(&stat.span());
+    match stat {
+        Statement::PrintStatement { expr, .. } => format!(
+            "{{\"kind\":\"Print\",\"span\":{},\"expression\":{}}}",
+            span,
+            expr_to_json(expr)
+        ),
+        Statement::ExprStatement { expr, .. } => format!(
+            "{{\"kind\":\"Expression\",\"span\":{},\"expression\":{}}}",
+            span,
+            expr_to_json(expr)
+        ),
+        Statement::DeclStatement {
+            name,
+            value,
+            constant,
+            annotation,
+            ..
+        } => format!(
+            "{{\"kind\":\"{}\",\"span\":{},\"name\":{},\"annotation\":{},\"initializer\":{}}}",
+            if *constant { "Const" } else { "Var" },
+            span,
+            token_to_json(name),
+            match annotation {
+                Some(annotation) => token_to_json(annotation),
+                None => "null".to_string(),
+            },
+            match value {
+                Some(expr) => expr_to_json(expr),
+                None => "null".to_string(),
+            }
+        ),
+        Statement::BlockStatement { stats, .. } => {
+            let items: Vec<String> = stats.iter().map(stat_to_json).collect();
+            format!(
+                "{{\"kind\":\"Block\",\"span\":{},\"statements\":[{}]}}",
+                span,
+                items.join(",")
+            )
+        }
+        Statement::ThrowStatement { expr, .. } => format!(
+            "{{\"kind\":\"Throw\",\"span\":{},\"expression\":{}}}",
+            span,
+            expr_to_json(expr)
+        ),
+        Statement::TryStatement {
+            body,
+            handler,
+            finally,
+            ..
+        } => format!(
+            "{{\"kind\":\"Try\",\"span\":{},\"body\":{},\"catch\":{},\"finally\":{}}}",
+            span,
+            stat_to_json(body),
+            match handler {
+                Some((name, catch_body)) => format!(
+                    "{{\"name\":{},\"body\":{}}}",
+                    token_to_json(name),
+                    stat_to_json(catch_body)
+                ),
+                None => "null".to_string(),
+            },
+            match finally {
+                Some(finally_body) => stat_to_json(finally_body),
+                None => "null".to_string(),
+            }
+        ),
+        Statement::MatchStatement { subject, arms, .. } => {
+            let items: Vec<String> = arms
+                .iter()
+                .map(|(pattern, body)| {
+                    format!(
+                        "{{\"pattern\":{},\"body\":{}}}",
+                        pattern_to_json(pattern),
+                        stat_to_json(body)
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"kind\":\"Match\",\"span\":{},\"subject\":{},\"arms\":[{}]}}",
+                span,
+                expr_to_json(subject),
+                items.join(",")
+            )
+        }
+        Statement::ImportStatement { name, path, .. } => format!(
+            "{{\"kind\":\"Import\",\"span\":{},\"name\":{},\"path\":{}}}",
+            span,
+            match name {
+                Some(name) => token_to_json(name),
+                None => "null".to_string(),
+            },
+            json_string(&path.literal.clone().unwrap_or_default())
+        ),
+    }
+}
+
+fn pattern_to_json(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Literal { value, .. } => format!(
+            "{{\"kind\":\"Literal\",\"type\":\"{}\",\"value\":{}}}",
+            value.get_type(),
+            literal_to_json(value)
+        ),
+        Pattern::Wildcard { .. } => "{\"kind\":\"Wildcard\"}".to_string(),
+        Pattern::Binding { name } => {
+            format!("{{\"kind\":\"Binding\",\"name\":{}}}", token_to_json(name))
+        }
+        Pattern::List { elements, .. } => {
+            let items: Vec<String> = elements.iter().map(pattern_to_json).collect();
+            format!("{{\"kind\":\"List\",\"elements\":[{}]}}", items.join(","))
+        }
+        Pattern::Alternatives { patterns } => {
+            let items: Vec<String> = patterns.iter().map(pattern_to_json).collect();
+            format!("{{\"kind\":\"Alternatives\",\"patterns\":[{}]}}", items.join(","))
+        }
+    }
+}
+
+fn expr_to_json(expr: &Expression) -> String {
+    let span = span_to_json(&expr.span());
+    match expr {
+        Expression::Literal { value, .. } => format!(
+            "{{\"kind\":\"Literal\",\"span\":{},\"type\":\"{}\",\"value\":{}}}",
+            span,
+            value.get_type(),
+            literal_to_json(value)
+        ),
+        Expression::Unary { operator, expr } => format!(
+            "{{\"kind\":\"Unary\",\"span\":{},\"operator\":{},\"operand\":{}}}",
+            span,
+            token_to_json(operator),
+            expr_to_json(expr)
+        ),
+        Expression::Binary {
+            operator,
+            left_expr,
+            right_expr,
+        } => format!(
+            "{{\"kind\":\"Binary\",\"span\":{},\"operator\":{},\"left\":{},\"right\":{}}}",
+            span,
+            token_to_json(operator),
+            expr_to_json(left_expr),
+            expr_to_json(right_expr)
+        ),
+        Expression::Grouping { expr, .. } => format!(
+            "{{\"kind\":\"Grouping\",\"span\":{},\"expression\":{}}}",
+            span,
+            expr_to_json(expr)
+        ),
+        Expression::Variable { variable } => format!(
+            "{{\"kind\":\"Variable\",\"span\":{},\"name\":{}}}",
+            span,
+            token_to_json(variable)
+        ),
+        Expression::Assignment { name, value } => format!(
+            "{{\"kind\":\"Assign\",\"span\":{},\"name\":{},\"value\":{}}}",
+            span,
+            token_to_json(name),
+            expr_to_json(value)
+        ),
+        Expression::CompoundAssignment {
+            target,
+            operator,
+            value,
+        } => format!(
+            "{{\"kind\":\"CompoundAssign\",\"span\":{},\"operator\":{},\"target\":{},\"value\":{}}}",
+            span,
+            token_to_json(operator),
+            expr_to_json(target),
+            expr_to_json(value)
+        ),
+        Expression::Conditional {
+            condition,
+            then_branch,
+            else_branch,
+        } => format!(
+            "{{\"kind\":\"Conditional\",\"span\":{},\"condition\":{},\"then\":{},\"else\":{}}}",
+            span,
+            expr_to_json(condition),
+            expr_to_json(then_branch),
+            expr_to_json(else_branch)
+        ),
+        Expression::ListLiteral { elements, .. } => {
+            let items: Vec<String> = elements.iter().map(expr_to_json).collect();
+            format!(
+                "{{\"kind\":\"List\",\"span\":{},\"elements\":[{}]}}",
+                span,
+                items.join(",")
+            )
+        }
+        Expression::MapLiteral { entries, .. } => {
+            let items: Vec<String> = entries
+                .iter()
+                .map(|(key, value)| {
+                    format!(
+                        "{{\"key\":{},\"value\":{}}}",
+                        expr_to_json(key),
+                        expr_to_json(value)
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"kind\":\"Map\",\"span\":{},\"entries\":[{}]}}",
+                span,
+                items.join(",")
+            )
+        }
+        Expression::Index { object, index, .. } => format!(
+            "{{\"kind\":\"Index\",\"span\":{},\"object\":{},\"index\":{}}}",
+            span,
+            expr_to_json(object),
+            expr_to_json(index)
+        ),
+        Expression::IndexAssignment {
+            object,
+            index,
+            value,
+        } => format!(
+            "{{\"kind\":\"IndexAssign\",\"span\":{},\"object\":{},\"index\":{},\"value\":{}}}",
+            span,
+            expr_to_json(object),
+            expr_to_json(index),
+            expr_to_json(value)
+        ),
+        Expression::MethodCall {
+            object,
+            name,
+            arguments,
+            ..
+        } => {
+            let items: Vec<String> = arguments.iter().map(expr_to_json).collect();
+            format!(
+                "{{\"kind\":\"MethodCall\",\"span\":{},\"object\":{},\"name\":{},\"arguments\":[{}]}}",
+                span,
+                expr_to_json(object),
+                token_to_json(name),
+                items.join(",")
+            )
+        }
+    }
+}
+
+fn push_line(out: &mut String, depth: usize, text: String) {
+    out.push_str(&"  ".repeat(depth));
+    out.push_str(&text);
+    out.push('\n');
+}
+
+fn stat_to_tree(stat: &Statement, depth: usize, out: &mut String) {
+    let span = stat.span();
+    match stat {
+        Statement::PrintStatement { expr, .. } => {
+            push_line(out, depth, format!("Print {}", span));
+            expr_to_tree(expr, depth + 1, out);
+        }
+        Statement::ExprStatement { expr, .. } => {
+            push_line(out, depth, format!("Expression {}", span));
+            expr_to_tree(expr, depth + 1, out);
+        }
+        Statement::DeclStatement {
+            name,
+            value,
+            constant,
+            annotation,
+            ..
+        } => {
+            let kind = if *constant { "Const" } else { "Var" };
+            let target = match annotation {
+                Some(annotation) => format!("{}: {}", name.lexeme, annotation.lexeme),
+                None => name.lexeme.clone(),
+            };
+            push_line(out, depth, format!("{} {} {}", kind, target, span));
+            if let Some(expr) = value {
+                expr_to_tree(expr, depth + 1, out);
+            }
+        }
+        Statement::BlockStatement { stats, .. } => {
+            push_line(out, depth, format!("Block {}", span));
+            for stat in stats {
+                stat_to_tree(stat, depth + 1, out);
+            }
+        }
+        Statement::ThrowStatement { expr, .. } => {
+            push_line(out, depth, format!("Throw {}", span));
+            expr_to_tree(expr, depth + 1, out);
+        }
+        Statement::TryStatement {
+            body,
+            handler,
+            finally,
+            ..
+        } => {
+            push_line(out, depth, format!("Try {}", span));
+            stat_to_tree(body, depth + 1, out);
+            if let Some((name, catch_body)) = handler {
+                push_line(out, depth + 1, format!("Catch {}", name.lexeme));
+                stat_to_tree(catch_body, depth + 2, out);
+            }
+            if let Some(finally_body) = finally {
+                push_line(out, depth + 1, "Finally".to_string());
+                stat_to_tree(finally_body, depth + 2, out);
+            }
+        }
+        Statement::MatchStatement { subject, arms, .. } => {
+            push_line(out, depth, format!("Match {}", span));
+            expr_to_tree(subject, depth + 1, out);
+            for (pattern, body) in arms {
+                push_line(out, depth + 1, format!("Arm {}", pattern));
+                stat_to_tree(body, depth + 2, out);
+            }
+        }
+        Statement::ImportStatement { name, path, .. } => {
+            let text = match name {
+                Some(name) => format!("Import {} from {} {}", name.lexeme, path.lexeme, span),
+                None => format!("Import {} {}", path.lexeme, span),
+            };
+            push_line(out, depth, text);
+        }
+    }
+}
+
+fn expr_to_tree(expr: &Expression, depth: usize, out: &mut String) {
+    let span = expr.span();
+    match expr {
+        Expression::Literal { value, .. } => {
+            let text = match value {
+                Literal::String(s) => json_string(s),
+                Literal::Number(n) => format_number_literal(*n),
+                Literal::Int(n) => format!("{}.0", n),
+                _ => format!("{}", value),
+            };
+            push_line(
+                out,
+                depth,
+                format!("Literal {} {} {}", value.get_type(), text, span),
+            );
+        }
+        Expression::Unary { operator, expr } => {
+            push_line(out, depth, format!("Unary {} {}", operator.lexeme, span));
+            expr_to_tree(expr, depth + 1, out);
+        }
+        Expression::Binary {
+            operator,
+            left_expr,
+            right_expr,
+        } => {
+            push_line(out, depth, format!("Binary {} {}", operator.lexeme, span));
+            expr_to_tree(left_expr, depth + 1, out);
+            expr_to_tree(right_expr, depth + 1, out);
+        }
+        Expression::Grouping { expr, .. } => {
+            push_line(out, depth, format!("Grouping {}", span));
+            expr_to_tree(expr, depth + 1, out);
+        }
+        Expression::Variable { variable } => {
+            push_line(out, depth, format!("Variable {} {}", variable.lexeme, span));
+        }
+        Expression::Assignment { name, value } => {
+            push_line(out, depth, format!("Assign {} {}", name.lexeme, span));
+            expr_to_tree(value, depth + 1, out);
+        }
+        Expression::CompoundAssignment {
+            target,
+            operator,
+            value,
+        } => {
+            push_line(out, depth, format!("CompoundAssign {} {}", operator.lexeme, span));
+            expr_to_tree(target, depth + 1, out);
+            expr_to_tree(value, depth + 1, out);
+        }
+        Expression::Conditional {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            push_line(out, depth, format!("Conditional {}", span));
+            expr_to_tree(condition, depth + 1, out);
+            expr_to_tree(then_branch, depth + 1, out);
+            expr_to_tree(else_branch, depth + 1, out);
+        }
+        Expression::ListLiteral { elements, .. } => {
+            push_line(out, depth, format!("List {}", span));
+            for element in elements {
+                expr_to_tree(element, depth + 1, out);
+            }
+        }
+        Expression::MapLiteral { entries, .. } => {
+            push_line(out, depth, format!("Map {}", span));
+            for (key, value) in entries {
+                push_line(out, depth + 1, "Entry".to_string());
+                expr_to_tree(key, depth + 2, out);
+                expr_to_tree(value, depth + 2, out);
+            }
+        }
+        Expression::Index { object, index, .. } => {
+            push_line(out, depth, format!("Index {}", span));
+            expr_to_tree(object, depth + 1, out);
+            expr_to_tree(index, depth + 1, out);
+        }
+        Expression::IndexAssignment {
+            object,
+            index,
+            value,
+        } => {
+            push_line(out, depth, format!("IndexAssign {}", span));
+            expr_to_tree(object, depth + 1, out);
+            expr_to_tree(index, depth + 1, out);
+            expr_to_tree(value, depth + 1, out);
+        }
+        Expression::MethodCall {
+            object,
+            name,
+            arguments,
+            ..
+        } => {
+            push_line(out, depth, format!("MethodCall {} {}", name.lexeme, span));
+            expr_to_tree(object, depth + 1, out);
+            for argument in arguments {
+                expr_to_tree(argument, depth + 1, out);
+            }
+        }
+    }
+}
@@ -1,46 +1,195 @@
 use crate::{
-    environment::{add_block_scoping, define_env, remove_block_scoping},
-    expr::{Expression, Literal, AST},
-    token::{Token, TokenType},
+    debugger,
+    environment::{add_block_scoping, define_const_env, define_env, remove_block_scoping},
+    expr::{number_literal, Expression, Literal, RuntimeError, AST},
+    map, module, optimizer,
+    pattern::Pattern,
+    token::{Span, Token, TokenType},
 };
 
+#[derive(Clone)]
 pub enum Statement {
-    PrintStatement(Expression),
-    ExprStatement(Expression),
-    DeclStatement { name: Token, value: Literal },
-    BlockStatement(Vec<Statement>),
+    PrintStatement { expr: Expression, span: Span },
+    ExprStatement { expr: Expression, span: Span },
+    DeclStatement {
+        name: Token,
+        value: Option<Expression>,
+        // Declared with `const` rather than `var`.
+        constant: bool,
+        // The optional `: type` after the name. Only the `check` command reads it.
+        annotation: Option<Token>,
+        span: Span,
+    },
+    BlockStatement { stats: Vec<Statement>, span: Span },
+    ThrowStatement { expr: Expression, span: Span },
+    TryStatement {
+        body: Box<Statement>,
+        // The name bound to the error and the block that handles it.
+        handler: Option<(Token, Box<Statement>)>,
+        finally: Option<Box<Statement>>,
+        span: Span,
+    },
+    MatchStatement {
+        subject: Expression,
+        arms: Vec<(Pattern, Statement)>,
+        span: Span,
+    },
+    // `import "path";` or `import name from "path";`
+    ImportStatement {
+        name: Option<Token>,
+        path: Token,
+        span: Span,
+    },
 }
 
-fn eval_print_stat(expr: &Expression) {
-    let lit = expr.accept();
+fn eval_print_stat(expr: &Expression) -> Result<(), RuntimeError> {
+    let lit = expr.accept()?;
     lit.print();
+    return Ok(());
 }
 
-fn eval_decl_stat(name: String, value: &Literal) {
-    define_env(name, value.clone());
+fn eval_decl_stat(name: String, value: &Option<Expression>, constant: bool) -> Result<(), RuntimeError> {
+    let lit = match value {
+        Some(expr) => expr.accept()?,
+        None => Literal::Nil,
+    };
+    if constant {
+        return define_const_env(name, lit);
+    }
+    return define_env(name, lit);
 }
 
-fn eval_expr_stat(expr: &Expression) {
-    expr.accept();
+fn eval_expr_stat(expr: &Expression) -> Result<(), RuntimeError> {
+    expr.accept()?;
+    return Ok(());
 }
 
-fn eval_block_stat(stats: &Vec<Statement>) {
+fn eval_block_stat(stats: &[Statement]) -> Result<(), RuntimeError> {
     add_block_scoping();
-    for stat in stats {
-        stat.accept();
-    }
+    debugger::enter_block();
+    // The scope is left even when an error unwinds through the block.
+    let result = stats.iter().try_for_each(|stat| stat.accept());
+    debugger::exit_block();
     remove_block_scoping();
+    return result;
+}
+
+fn eval_throw_stat(expr: &Expression) -> Result<(), RuntimeError> {
+    let value = expr.accept()?;
+    return Err(RuntimeError::Thrown {
+        value,
+        line: None,
+        file: None,
+    });
+}
+
+// Runs the first arm whose pattern matches, with its bindings in a new scope.
+// A value no arm matches is ignored, like an `if` without an `else`.
+fn eval_match_stat(subject: &Expression, arms: &[(Pattern, Statement)]) -> Result<(), RuntimeError> {
+    let value = subject.accept()?;
+    for (pattern, body) in arms {
+        let mut bindings = Vec::new();
+        if !pattern.matches(&value, &mut bindings) {
+            continue;
+        }
+
+        add_block_scoping();
+        let result = bindings
+            .into_iter()
+            .try_for_each(|(name, bound)| define_env(name, bound))
+            .and_then(|_| body.accept());
+        remove_block_scoping();
+        return result;
+    }
+    return Ok(());
+}
+
+fn eval_import_stat(name: &Option<Token>, path: &Token) -> Result<(), RuntimeError> {
+    let module = module::load(&path.literal.clone().unwrap_or_default())?;
+    match (name, &module.value) {
+        (Some(name), _) => define_env(name.lexeme.clone(), module.value.clone()),
+        // A bare import keeps the module's constants constant.
+        (None, Literal::Map(entries)) => {
+            for (key, value) in map::entries(entries) {
+                let key = key.to_lox_string();
+                if module.constants.contains(&key) {
+                    define_const_env(key, value)?;
+                } else {
+                    define_env(key, value)?;
+                }
+            }
+            return Ok(());
+        }
+        (None, _) => Ok(()),
+    }
+}
+
+fn eval_try_stat(
+    body: &Statement,
+    handler: &Option<(Token, Box<Statement>)>,
+    finally: &Option<Box<Statement>>,
+) -> Result<(), RuntimeError> {
+    let result = match (body.accept(), handler) {
+        (Err(error), Some((name, catch_body))) => {
+            add_block_scoping();
+            let caught = define_env(name.lexeme.clone(), error.to_value())
+                .and_then(|_| catch_body.accept());
+            remove_block_scoping();
+            caught
+        }
+        (result, _) => result,
+    };
+
+    // An error raised by `finally` replaces whatever the try or catch produced.
+    if let Some(finally_body) = finally {
+        finally_body.accept()?;
+    }
+    return result;
 }
 
 impl Statement {
-    pub fn accept(&self) {
+    pub fn span(&self) -> Span {
         match self {
-            Statement::PrintStatement(expr) => eval_print_stat(expr),
-            Statement::DeclStatement { name, value } => eval_decl_stat(name.lexeme.clone(), value),
-            Statement::ExprStatement(expr) => eval_expr_stat(expr),
-            Statement::BlockStatement(stats) => eval_block_stat(stats),
+            Statement::PrintStatement { span, .. } => *span,
+            Statement::ExprStatement { span, .. } => *span,
+            Statement::DeclStatement { span, .. } => *span,
+            Statement::BlockStatement { span, .. } => *span,
+            Statement::ThrowStatement { span, .. } => *span,
+            Statement::TryStatement { span, .. } => *span,
+            Statement::MatchStatement { span, .. } => *span,
+            Statement::ImportStatement { span, .. } => *span,
         }
     }
+
+    pub fn line(&self) -> i32 {
+        return self.span().line;
+    }
+
+    pub fn accept(&self) -> Result<(), RuntimeError> {
+        debugger::before_statement(self.line());
+
+        let result = match self {
+            Statement::PrintStatement { expr, .. } => eval_print_stat(expr),
+            Statement::DeclStatement {
+                name,
+                value,
+                constant,
+                ..
+            } => eval_decl_stat(name.lexeme.clone(), value, *constant),
+            Statement::ExprStatement { expr, .. } => eval_expr_stat(expr),
+            Statement::BlockStatement { stats, .. } => eval_block_stat(stats),
+            Statement::ThrowStatement { expr, .. } => eval_throw_stat(expr),
+            Statement::TryStatement {
+                body,
+                handler,
+                finally,
+                ..
+            } => eval_try_stat(body, handler, finally),
+            Statement::MatchStatement { subject, arms, .. } => eval_match_stat(subject, arms),
+            Statement::ImportStatement { name, path, .. } => eval_import_stat(name, path),
+        };
+        return result.map_err(|error| error.with_line(self.line()));
+    }
 }
 
 pub struct SST {
@@ -57,34 +206,66 @@ impl SST {
     }
 
     fn print_stat(&mut self) -> Statement {
+        let keyword = self.ast.peek();
         self.ast.advance();
 
         let expr = self.ast.expression();
-        self.ast
+        let semicolon = self
+            .ast
             .consume(TokenType::SEMICOLON, "expected semicolon".to_string());
-        return Statement::PrintStatement(expr);
+        let span = keyword.span().merge(semicolon.span());
+        return Statement::PrintStatement { expr, span };
     }
 
     fn declare_stat(&mut self) -> Statement {
+        let keyword = self.ast.peek();
+        let constant = keyword.token_type == TokenType::CONST;
         self.ast.advance();
         let name = self
             .ast
             .consume(TokenType::IDENTIFIER, "Expect variable name.".to_string());
 
-        let mut value = Literal::Nil;
+        let mut annotation = None;
+        if self.ast.match_type(&[TokenType::COLON]) {
+            self.ast.advance();
+            // `nil` is a keyword, every other type name is an identifier.
+            if self.ast.match_type(&[TokenType::IDENTIFIER, TokenType::NIL]) {
+                annotation = Some(self.ast.peek());
+                self.ast.advance();
+            } else {
+                self.ast
+                    .syntax_error("Expect type name after ':'.".to_string());
+            }
+        }
+
+        let mut value = None;
 
         if self.ast.match_type(&[TokenType::EQUAL]) {
             self.ast.advance();
-            value = self.ast.expression().accept();
+            value = Some(self.ast.expression());
+        } else if constant {
+            self.ast.syntax_error(format!(
+                "[line {}] Error at '{}': Constant must be initialized.",
+                name.line, name.lexeme
+            ));
         }
 
-        self.ast
+        let semicolon = self
+            .ast
             .consume(TokenType::SEMICOLON, "expected semicolon".to_string());
+        let span = keyword.span().merge(semicolon.span());
 
-        return Statement::DeclStatement { name, value };
+        return Statement::DeclStatement {
+            name,
+            value,
+            constant,
+            annotation,
+            span,
+        };
     }
 
     fn block_stat(&mut self) -> Statement {
+        let left_brace = self.ast.peek();
         self.ast.advance();
         let mut stats = Vec::new();
 
@@ -93,10 +274,289 @@ impl SST {
             stats.push(stat);
         }
 
-        self.ast
+        let right_brace = self
+            .ast
             .consume(TokenType::RIGHT_BRACE, "Expect '}' after block.".to_string());
+        let span = left_brace.span().merge(right_brace.span());
+
+        return Statement::BlockStatement { stats, span };
+    }
+
+    fn throw_stat(&mut self) -> Statement {
+        let keyword = self.ast.peek();
+        self.ast.advance();
 
-        return Statement::BlockStatement(stats);
+        let expr = self.ast.expression();
+        let semicolon = self
+            .ast
+            .consume(TokenType::SEMICOLON, "expected semicolon".to_string());
+        let span = keyword.span().merge(semicolon.span());
+        return Statement::ThrowStatement { expr, span };
+    }
+
+    fn pattern_alternative(&mut self) -> Pattern {
+        let token = self.ast.peek();
+        let literal_types = [
+            TokenType::NUMBER,
+            TokenType::STRING,
+            TokenType::TRUE,
+            TokenType::FALSE,
+            TokenType::NIL,
+        ];
+
+        if self.ast.match_type(&literal_types) {
+            self.ast.advance();
+            let value = match token.token_type {
+                TokenType::NUMBER => number_literal(&token),
+                TokenType::STRING => Literal::String(token.literal.clone().unwrap_or_default()),
+                TokenType::TRUE => Literal::Bool(true),
+                TokenType::FALSE => Literal::Bool(false),
+                _ => Literal::Nil,
+            };
+            return Pattern::Literal { value, token };
+        }
+
+        if self.ast.match_type(&[TokenType::MINUS]) {
+            self.ast.advance();
+            let number = self
+                .ast
+                .consume(TokenType::NUMBER, "Expect number after '-' in pattern.".to_string());
+            let value = match number_literal(&number) {
+                Literal::Int(n) => Literal::Int(-n),
+                Literal::Number(n) => Literal::Number(-n),
+                value => value,
+            };
+            return Pattern::Literal { value, token };
+        }
+
+        if self.ast.match_type(&[TokenType::IDENTIFIER]) {
+            self.ast.advance();
+            if token.lexeme == "_" {
+                return Pattern::Wildcard { token };
+            }
+            return Pattern::Binding { name: token };
+        }
+
+        if self.ast.match_type(&[TokenType::LEFT_BRACKET]) {
+            self.ast.advance();
+            let mut elements = Vec::new();
+            while !self.ast.check(TokenType::RIGHT_BRACKET) {
+                elements.push(Self::pattern(self));
+                if !self.ast.match_type(&[TokenType::COMMA]) {
+                    break;
+                }
+                self.ast.advance();
+            }
+            self.ast.consume(
+                TokenType::RIGHT_BRACKET,
+                "Expect ']' after list pattern.".to_string(),
+            );
+            return Pattern::List {
+                elements,
+                bracket: token,
+            };
+        }
+
+        self.ast.syntax_error(format!(
+            "[line {}] Error at '{}': Expect pattern.",
+            token.line, token.lexeme
+        ));
+        return Pattern::Wildcard { token };
+    }
+
+    fn pattern(&mut self) -> Pattern {
+        let mut patterns = vec![Self::pattern_alternative(self)];
+        while self.ast.match_type(&[TokenType::PIPE]) {
+            self.ast.advance();
+            patterns.push(Self::pattern_alternative(self));
+        }
+
+        if patterns.len() == 1 {
+            return patterns.remove(0);
+        }
+        return Pattern::Alternatives { patterns };
+    }
+
+    fn match_stat(&mut self) -> Statement {
+        let keyword = self.ast.peek();
+        self.ast.advance();
+
+        self.ast
+            .consume(TokenType::LEFT_PAREN, "Expect '(' after 'match'.".to_string());
+        let subject = self.ast.expression();
+        self.ast
+            .consume(TokenType::RIGHT_PAREN, "Expect ')' after match value.".to_string());
+        self.ast
+            .consume(TokenType::LEFT_BRACE, "Expect '{' before match arms.".to_string());
+
+        let mut arms: Vec<(Pattern, Statement)> = Vec::new();
+        while !self.ast.is_at_end() && !self.ast.check(TokenType::RIGHT_BRACE) {
+            let pattern = Self::pattern(self);
+            self.ast
+                .consume(TokenType::EQUAL_GREATER, "Expect '=>' after pattern.".to_string());
+            let (body, separated) = Self::arm_body(self);
+
+            if arms.iter().any(|(earlier, _)| earlier.is_irrefutable()) {
+                eprintln!("[line {}] Warning: Unreachable match arm.", pattern.line());
+            }
+            arms.push((pattern, body));
+
+            if self.ast.match_type(&[TokenType::COMMA, TokenType::SEMICOLON]) {
+                self.ast.advance();
+            } else if !separated && !self.ast.check(TokenType::RIGHT_BRACE) {
+                self.ast
+                    .syntax_error("Expect ',' or ';' after match arm.".to_string());
+            }
+        }
+
+        let right_brace = self
+            .ast
+            .consume(TokenType::RIGHT_BRACE, "Expect '}' after match arms.".to_string());
+
+        // Values are dynamically typed, so only a catch-all arm covers every case.
+        if !arms.iter().any(|(pattern, _)| pattern.is_irrefutable()) {
+            eprintln!(
+                "[line {}] Warning: Match is not exhaustive; add a '_' arm.",
+                keyword.line
+            );
+        }
+
+        let span = keyword.span().merge(right_brace.span());
+        return Statement::MatchStatement {
+            subject,
+            arms,
+            span,
+        };
+    }
+
+    // A print, throw or expression arm ends at the `,` or `;` before the next
+    // arm, as in `1 => print "one", _ => print "other"`. Any other statement,
+    // like a block, is parsed whole. Also returns whether the body already
+    // ended itself, so no separator is needed after it.
+    fn arm_body(&mut self) -> (Statement, bool) {
+        let keyword = self.ast.peek();
+        let statement_types = [
+            TokenType::VAR,
+            TokenType::CONST,
+            TokenType::MATCH,
+            TokenType::IMPORT,
+            TokenType::TRY,
+            TokenType::BREAK,
+            TokenType::CONTINUE,
+        ];
+        let is_block = self.ast.match_type(&[TokenType::LEFT_BRACE]) && !Self::is_map_literal(self);
+        if is_block || self.ast.match_type(&statement_types) {
+            return (Self::statement(self), true);
+        }
+
+        if self.ast.match_type(&[TokenType::PRINT, TokenType::THROW]) {
+            self.ast.advance();
+        }
+        let expr = self.ast.expression();
+        let span = keyword.span().merge(expr.span());
+        let body = match keyword.token_type {
+            TokenType::PRINT => Statement::PrintStatement { expr, span },
+            TokenType::THROW => Statement::ThrowStatement { expr, span },
+            _ => Statement::ExprStatement { expr, span },
+        };
+        return (body, false);
+    }
+
+    fn import_stat(&mut self) -> Statement {
+        let keyword = self.ast.peek();
+        self.ast.advance();
+
+        let mut name = None;
+        if self.ast.match_type(&[TokenType::IDENTIFIER]) {
+            name = Some(self.ast.peek());
+            self.ast.advance();
+            // `from` is only a keyword here, so it stays usable as a variable name.
+            let from = self
+                .ast
+                .consume(TokenType::IDENTIFIER, "Expect 'from' after import name.".to_string());
+            if from.lexeme != "from" {
+                self.ast
+                    .syntax_error("Expect 'from' after import name.".to_string());
+            }
+        }
+
+        let path = self
+            .ast
+            .consume(TokenType::STRING, "Expect module path.".to_string());
+        let semicolon = self
+            .ast
+            .consume(TokenType::SEMICOLON, "expected semicolon".to_string());
+        let span = keyword.span().merge(semicolon.span());
+        return Statement::ImportStatement { name, path, span };
+    }
+
+    fn try_block(&mut self, message: &str) -> Statement {
+        if !self.ast.match_type(&[TokenType::LEFT_BRACE]) {
+            self.ast.syntax_error(message.to_string());
+            return Statement::BlockStatement {
+                stats: Vec::new(),
+                span: self.ast.peek().span(),
+            };
+        }
+        return Self::block_stat(self);
+    }
+
+    fn try_stat(&mut self) -> Statement {
+        let keyword = self.ast.peek();
+        self.ast.advance();
+
+        let body = Self::try_block(self, "Expect '{' after 'try'.");
+        let mut span = keyword.span().merge(body.span());
+
+        let mut handler = None;
+        if self.ast.match_type(&[TokenType::CATCH]) {
+            self.ast.advance();
+            self.ast
+                .consume(TokenType::LEFT_PAREN, "Expect '(' after 'catch'.".to_string());
+            let name = self
+                .ast
+                .consume(TokenType::IDENTIFIER, "Expect error variable name.".to_string());
+            self.ast.consume(
+                TokenType::RIGHT_PAREN,
+                "Expect ')' after error variable name.".to_string(),
+            );
+            let catch_body = Self::try_block(self, "Expect '{' before catch body.");
+            span = span.merge(catch_body.span());
+            handler = Some((name, Box::new(catch_body)));
+        }
+
+        let mut finally = None;
+        if self.ast.match_type(&[TokenType::FINALLY]) {
+            self.ast.advance();
+            let finally_body = Self::try_block(self, "Expect '{' after 'finally'.");
+            span = span.merge(finally_body.span());
+            finally = Some(Box::new(finally_body));
+        }
+
+        if handler.is_none() && finally.is_none() {
+            self.ast
+                .syntax_error("Expect 'catch' or 'finally' after try block.".to_string());
+        }
+
+        return Statement::TryStatement {
+            body: Box::new(body),
+            handler,
+            finally,
+            span,
+        };
+    }
+
+    fn is_map_literal(&self) -> bool {
+        let key_types = [
+            TokenType::STRING,
+            TokenType::NUMBER,
+            TokenType::IDENTIFIER,
+            TokenType::TRUE,
+            TokenType::FALSE,
+            TokenType::NIL,
+        ];
+        return key_types.iter().any(|key| self.ast.check_ahead(1, key.clone()))
+            && self.ast.check_ahead(2, TokenType::COLON);
     }
 
     fn statement(&mut self) -> Statement {
@@ -104,25 +564,102 @@ impl SST {
             return Self::print_stat(self);
         }
 
-        if self.ast.match_type(&[TokenType::VAR]) {
+        if self.ast.match_type(&[TokenType::VAR, TokenType::CONST]) {
             return Self::declare_stat(self);
         }
 
-        if self.ast.match_type(&[TokenType::LEFT_BRACE]) {
+        if self.ast.match_type(&[TokenType::MATCH]) {
+            return Self::match_stat(self);
+        }
+
+        if self.ast.match_type(&[TokenType::IMPORT]) {
+            return Self::import_stat(self);
+        }
+
+        if self.ast.match_type(&[TokenType::THROW]) {
+            return Self::throw_stat(self);
+        }
+
+        if self.ast.match_type(&[TokenType::TRY]) {
+            return Self::try_stat(self);
+        }
+
+        // There are no loop statements yet, so `break` and `continue` are never
+        // inside a loop body.
+        if self.ast.match_type(&[TokenType::BREAK, TokenType::CONTINUE]) {
+            let keyword = self.ast.peek();
+            self.ast.syntax_error(format!(
+                "[line {}] Error at '{}': Can't use '{}' outside of a loop.",
+                keyword.line, keyword.lexeme, keyword.lexeme
+            ));
+        }
+
+        // `{ key: value }` at the start of a statement is a map literal, not a block.
+        if self.ast.match_type(&[TokenType::LEFT_BRACE]) && !Self::is_map_literal(self) {
             return Self::block_stat(self);
         }
 
         let expr = self.ast.expression();
-        self.ast
+        let semicolon = self
+            .ast
             .consume(TokenType::SEMICOLON, "expected semicolon".to_string());
-        return Statement::ExprStatement(expr);
+        let span = expr.span().merge(semicolon.span());
+        return Statement::ExprStatement { expr, span };
     }
 
     pub fn parse_tree(&mut self) {
+        if let Err(error) = Self::run(self) {
+            error.report();
+        }
+    }
+
+    // Parses and runs the program one statement at a time, stopping at the
+    // first error nothing caught.
+    pub fn run(&mut self) -> Result<(), RuntimeError> {
+        while !self.ast.is_at_end() {
+            let stat = Self::prepare(Self::statement(self));
+            stat.accept()?;
+            self.stats.push(stat);
+        }
+        return Ok(());
+    }
+
+    fn prepare(stat: Statement) -> Statement {
+        if optimizer::is_enabled() {
+            return optimizer::optimize_stat(stat);
+        }
+        return stat;
+    }
+
+    // Parses the whole program, returning the first syntax error instead of
+    // ending the process. Imported modules are parsed this way before any of
+    // them runs.
+    pub fn try_parse_program(&mut self) -> Result<(), String> {
+        self.ast.set_recover(true);
+        Self::parse_program(self);
+        match self.ast.take_error() {
+            Some(message) => Err(message),
+            None => Ok(()),
+        }
+    }
+
+    // Runs the statements `parse_program` collected.
+    pub fn run_parsed(&self) -> Result<(), RuntimeError> {
+        for stat in &self.stats {
+            Self::prepare(stat.clone()).accept()?;
+        }
+        return Ok(());
+    }
+
+    // Parses the whole program without running any of it.
+    pub fn parse_program(&mut self) {
         while !self.ast.is_at_end() {
             let stat = Self::statement(self);
-            stat.accept();
             self.stats.push(stat);
         }
     }
+
+    pub fn export_stats(&self) -> Vec<Statement> {
+        return self.stats.clone();
+    }
 }
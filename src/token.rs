@@ -1,6 +1,9 @@
-pub const RESERVED_WORDS: [&str; 16] = [
-    "and", "class", "else", "false", "for", "fun", "if", "nil", "or", "print", "return", "super",
-    "this", "true", "var", "while",
+use crate::expr::format_number_literal;
+
+pub const RESERVED_WORDS: [&str; 25] = [
+    "and", "break", "catch", "class", "const", "continue", "else", "false", "finally", "for",
+    "fun", "if", "import", "match", "nil", "or", "print", "return", "super", "this", "throw",
+    "true", "try", "var", "while",
 ];
 
 #[derive(Debug, PartialEq, Clone)]
@@ -10,23 +13,42 @@ pub enum TokenType {
     RIGHT_PAREN,
     LEFT_BRACE,
     RIGHT_BRACE,
+    LEFT_BRACKET,
+    RIGHT_BRACKET,
     COMMA,
     DOT,
     MINUS,
     PLUS,
     SEMICOLON,
+    COLON,
+    QUESTION,
     SLASH,
     STAR,
+    PERCENT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
 
     // One or two character tokens.
     BANG,
     BANG_EQUAL,
     EQUAL,
     EQUAL_EQUAL,
+    EQUAL_GREATER,
     GREATER,
     GREATER_EQUAL,
     LESS,
     LESS_EQUAL,
+    LESS_LESS,
+    GREATER_GREATER,
+    STAR_STAR,
+    TILDE_SLASH,
+    PLUS_EQUAL,
+    MINUS_EQUAL,
+    STAR_EQUAL,
+    SLASH_EQUAL,
+    PERCENT_EQUAL,
 
     // Literals.
     IDENTIFIER,
@@ -35,19 +57,28 @@ pub enum TokenType {
 
     // Keywords.
     AND,
+    BREAK,
+    CATCH,
     CLASS,
+    CONST,
+    CONTINUE,
     ELSE,
     FALSE,
+    FINALLY,
     FUN,
     FOR,
     IF,
+    IMPORT,
+    MATCH,
     NIL,
     OR,
     PRINT,
     RETURN,
     SUPER,
     THIS,
+    THROW,
     TRUE,
+    TRY,
     VAR,
     WHILE,
 
@@ -61,33 +92,103 @@ pub enum TokenType {
 //     Number(i32),
 // }
 
+// Source range covered by a token or syntax node. Lines and columns start at 1,
+// the end column points just past the last character.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub line: i32,
+    pub column: i32,
+    pub end_line: i32,
+    pub end_column: i32,
+}
+
+impl Span {
+    pub fn merge(&self, other: Span) -> Span {
+        let start = if (other.line, other.column) < (self.line, self.column) {
+            other
+        } else {
+            *self
+        };
+        let end = if (other.end_line, other.end_column) > (self.end_line, self.end_column) {
+            other
+        } else {
+            *self
+        };
+        Span {
+            line: start.line,
+            column: start.column,
+            end_line: end.end_line,
+            end_column: end.end_column,
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}-{}:{}", self.line, self.column, self.end_line, self.end_column)
+    }
+}
+
 #[derive(Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub literal: Option<String>,
+    pub line: i32,
+    pub column: i32,
+    // Byte offset of the lexeme in the source.
+    pub offset: usize,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: Option<String>) -> Token {
+    pub fn new(
+        token_type: TokenType,
+        lexeme: String,
+        literal: Option<String>,
+        line: i32,
+        column: i32,
+        offset: usize,
+    ) -> Token {
         Token {
             token_type,
             lexeme,
-            literal
+            literal,
+            line,
+            column,
+            offset,
+        }
+    }
+
+    pub fn byte_range(&self) -> (usize, usize) {
+        return (self.offset, self.offset + self.lexeme.len());
+    }
+
+    pub fn span(&self) -> Span {
+        let mut end_line = self.line;
+        let mut end_column = self.column;
+        for char in self.lexeme.chars() {
+            if char == '\n' {
+                end_line += 1;
+                end_column = 1;
+            } else {
+                end_column += 1;
+            }
+        }
+        Span {
+            line: self.line,
+            column: self.column,
+            end_line,
+            end_column,
         }
     }
 
     pub fn to_string(&self) {
         let copied_literal = self.literal.clone();
         if self.token_type == TokenType::NUMBER {
-            let literal_number = copied_literal
-                .unwrap_or_default()
-                .parse::<f64>()
-                .unwrap_or_default();
-            let formatted = if literal_number.fract() == 0.0 {
-                format!("{:.1}", literal_number) // Ensure at least one decimal place
-            } else {
-                literal_number.to_string() // Use the default string representation
+            let literal = copied_literal.unwrap_or_default();
+            let formatted = match literal.parse::<i64>() {
+                Ok(n) => format!("{}.0", n),
+                Err(_) => format_number_literal(literal.parse::<f64>().unwrap_or_default()),
             };
             println!("{:#?} {} {}", self.token_type, self.lexeme, formatted)
         } else {
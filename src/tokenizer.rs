@@ -1,15 +1,39 @@
-use crate::token::{
-    TokenType,
-    Token,
+use crate::{
+    printer::{json_string, number_to_json},
+    token::{Token, TokenType},
 };
 
+pub struct LexError {
+    pub message: String,
+    pub line: i32,
+    pub column: i32,
+    pub start: usize,
+    pub end: usize,
+}
+
 pub struct Tokenizer {
-    tokens: Vec<Token>
+    tokens: Vec<Token>,
+    errors: Vec<LexError>,
+    // Collect errors without printing them, for modules that report through the importer.
+    quiet: bool,
+    line: i32,
+    // Position of the first character of the token being scanned.
+    start_line: i32,
+    start_column: i32,
+    start_offset: usize,
 }
 
 impl Tokenizer {
     pub fn new() -> Tokenizer {
-        return Tokenizer { tokens: Vec::new() };
+        return Tokenizer {
+            tokens: Vec::new(),
+            errors: Vec::new(),
+            quiet: false,
+            line: 1,
+            start_line: 1,
+            start_column: 1,
+            start_offset: 0,
+        };
     }
 
     fn is_digit(char: char) -> bool {
@@ -42,19 +66,28 @@ impl Tokenizer {
     fn get_reserverd_word_token_type(char: &str) -> TokenType {
         match char {
             "and" => TokenType::AND,
+            "break" => TokenType::BREAK,
+            "catch" => TokenType::CATCH,
             "class" => TokenType::CLASS,
+            "const" => TokenType::CONST,
+            "continue" => TokenType::CONTINUE,
             "else" => TokenType::ELSE,
             "false" => TokenType::FALSE,
+            "finally" => TokenType::FINALLY,
             "for" => TokenType::FOR,
             "fun" => TokenType::FUN,
             "if" => TokenType::IF,
+            "import" => TokenType::IMPORT,
+            "match" => TokenType::MATCH,
             "nil" => TokenType::NIL,
             "or" => TokenType::OR,
             "print" => TokenType::PRINT,
             "return" => TokenType::RETURN,
             "super" => TokenType::SUPER,
             "this" => TokenType::THIS,
+            "throw" => TokenType::THROW,
             "true" => TokenType::TRUE,
+            "try" => TokenType::TRY,
             "var" => TokenType::VAR,
             "while" => TokenType::WHILE,
             _ => TokenType::IDENTIFIER,
@@ -66,20 +99,36 @@ impl Tokenizer {
             token_type,
             lexeme,
             literal,
+            self.start_line,
+            self.start_column,
+            self.start_offset,
         );
         let _ = &self.tokens.push(token);
     }
 
-    fn lexer_error(line: i32, message: String) {
-        eprintln!("[line {}] Error: {}", line, message)
+    fn lexer_error(&mut self, message: String, end: usize) {
+        if !self.quiet {
+            eprintln!("[line {}] Error: {}", self.line, message);
+        }
+        self.errors.push(LexError {
+            message,
+            line: self.start_line,
+            column: self.start_column,
+            start: self.start_offset,
+            end,
+        });
     }
 
     pub fn scan(&mut self, file_contents: String) -> i32 {
         let mut result = 0;
-        let mut line = 1;
 
         let mut index: usize = 0;
+        let mut line_start: usize = 0;
         let file_contents_len = file_contents.len();
+        let byte_offsets: Vec<usize> = file_contents.char_indices().map(|(i, _)| i).collect();
+        let byte_offset = |idx: usize| {
+            return byte_offsets.get(idx).copied().unwrap_or(file_contents_len);
+        };
 
         let char_at = |idx: usize| {
             return file_contents.chars().nth(idx).unwrap_or_default();
@@ -87,17 +136,84 @@ impl Tokenizer {
 
         while index < file_contents_len {
             let char = file_contents.chars().nth(index).unwrap_or_default();
+            self.start_line = self.line;
+            self.start_column = (index - line_start) as i32 + 1;
+            self.start_offset = byte_offset(index);
 
             match char {
                 '(' => Self::add_token(self, TokenType::LEFT_PAREN, String::from(char), None),
                 ')' => Self::add_token(self, TokenType::RIGHT_PAREN, String::from(char), None),
                 '{' => Self::add_token(self, TokenType::LEFT_BRACE, String::from(char), None),
                 '}' => Self::add_token(self, TokenType::RIGHT_BRACE, String::from(char), None),
+                '[' => Self::add_token(self, TokenType::LEFT_BRACKET, String::from(char), None),
+                ']' => Self::add_token(self, TokenType::RIGHT_BRACKET, String::from(char), None),
                 ',' => Self::add_token(self, TokenType::COMMA, String::from(char), None),
                 ';' => Self::add_token(self, TokenType::SEMICOLON, String::from(char), None),
-                '+' => Self::add_token(self, TokenType::PLUS, String::from(char), None),
-                '-' => Self::add_token(self, TokenType::MINUS, String::from(char), None),
-                '*' => Self::add_token(self, TokenType::STAR, String::from(char), None),
+                ':' => Self::add_token(self, TokenType::COLON, String::from(char), None),
+                '?' => Self::add_token(self, TokenType::QUESTION, String::from(char), None),
+                '+' => {
+                    if index + 1 < file_contents_len && char_at(index + 1) == '=' {
+                        Self::add_token(
+                            self,
+                            TokenType::PLUS_EQUAL,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
+                    } else {
+                        Self::add_token(self, TokenType::PLUS, String::from(char), None);
+                    }
+                }
+                '-' => {
+                    if index + 1 < file_contents_len && char_at(index + 1) == '=' {
+                        Self::add_token(
+                            self,
+                            TokenType::MINUS_EQUAL,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
+                    } else {
+                        Self::add_token(self, TokenType::MINUS, String::from(char), None);
+                    }
+                }
+                '*' => {
+                    if index + 1 < file_contents_len && char_at(index + 1) == '*' {
+                        Self::add_token(
+                            self,
+                            TokenType::STAR_STAR,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
+                    } else if index + 1 < file_contents_len && char_at(index + 1) == '=' {
+                        Self::add_token(
+                            self,
+                            TokenType::STAR_EQUAL,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
+                    } else {
+                        Self::add_token(self, TokenType::STAR, String::from(char), None);
+                    }
+                }
+                '&' => Self::add_token(self, TokenType::AMPERSAND, String::from(char), None),
+                '|' => Self::add_token(self, TokenType::PIPE, String::from(char), None),
+                '^' => Self::add_token(self, TokenType::CARET, String::from(char), None),
+                '%' => {
+                    if index + 1 < file_contents_len && char_at(index + 1) == '=' {
+                        Self::add_token(
+                            self,
+                            TokenType::PERCENT_EQUAL,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
+                    } else {
+                        Self::add_token(self, TokenType::PERCENT, String::from(char), None);
+                    }
+                }
                 '.' => Self::add_token(self, TokenType::DOT, String::from(char), None),
                 '=' => {
                     if index + 1 < file_contents_len && char_at(index + 1) == '=' {
@@ -108,6 +224,14 @@ impl Tokenizer {
                             None,
                         );
                         index += 1;
+                    } else if index + 1 < file_contents_len && char_at(index + 1) == '>' {
+                        Self::add_token(
+                            self,
+                            TokenType::EQUAL_GREATER,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
                     } else {
                         Self::add_token(self, TokenType::EQUAL, String::from(char), None);
                     }
@@ -121,6 +245,14 @@ impl Tokenizer {
                             None,
                         );
                         index += 1;
+                    } else if index + 1 < file_contents_len && char_at(index + 1) == '<' {
+                        Self::add_token(
+                            self,
+                            TokenType::LESS_LESS,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
                     } else {
                         Self::add_token(self, TokenType::LESS, String::from(char), None);
                     }
@@ -134,6 +266,14 @@ impl Tokenizer {
                             None,
                         );
                         index += 1;
+                    } else if index + 1 < file_contents_len && char_at(index + 1) == '>' {
+                        Self::add_token(
+                            self,
+                            TokenType::GREATER_GREATER,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
                     } else {
                         Self::add_token(self, TokenType::GREATER, String::from(char), None);
                     }
@@ -151,11 +291,32 @@ impl Tokenizer {
                         Self::add_token(self, TokenType::BANG, String::from(char), None);
                     }
                 }
+                '~' => {
+                    if index + 1 < file_contents_len && char_at(index + 1) == '/' {
+                        Self::add_token(
+                            self,
+                            TokenType::TILDE_SLASH,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
+                    } else {
+                        Self::add_token(self, TokenType::TILDE, String::from(char), None);
+                    }
+                }
                 '/' => {
                     if index + 1 < file_contents_len && char_at(index + 1) == '/' {
                         while index + 1 < file_contents_len && char_at(index + 1) != '\n' {
                             index += 1;
                         }
+                    } else if index + 1 < file_contents_len && char_at(index + 1) == '=' {
+                        Self::add_token(
+                            self,
+                            TokenType::SLASH_EQUAL,
+                            String::from(char.to_string() + &char_at(index + 1).to_string()),
+                            None,
+                        );
+                        index += 1;
                     } else {
                         Self::add_token(self, TokenType::SLASH, String::from(char), None);
                     }
@@ -164,6 +325,10 @@ impl Tokenizer {
                     let mut lexeme = String::from("");
                     index += 1;
                     while index < file_contents_len && char_at(index) != '"' {
+                        if char_at(index) == '\n' {
+                            self.line += 1;
+                            line_start = index + 1;
+                        }
                         lexeme = lexeme + &char_at(index).to_string();
                         index += 1;
                     }
@@ -173,7 +338,11 @@ impl Tokenizer {
                         let lexeme_format = format!("\"{}\"", lexeme.clone());
                         Self::add_token(self, TokenType::STRING, lexeme_format, Some(literal));
                     } else {
-                        Self::lexer_error(line, String::from("Unterminated string."));
+                        Self::lexer_error(
+                            self,
+                            String::from("Unterminated string."),
+                            file_contents_len,
+                        );
                         result = 65;
                     }
                 }
@@ -182,14 +351,53 @@ impl Tokenizer {
                 '\r' => {}
                 '\0' => {}
                 '\n' => {
-                    line += 1;
+                    self.line += 1;
+                    line_start = index + 1;
                 }
                 _ => {
-                    if Self::is_digit(char) {
+                    let radix = match char_at(index + 1) {
+                        'x' | 'X' if char == '0' => 16,
+                        'b' | 'B' if char == '0' => 2,
+                        _ => 10,
+                    };
+
+                    if Self::is_digit(char) && radix != 10 {
+                        let mut lexeme = char.to_string() + &char_at(index + 1).to_string();
+                        let mut digits = String::from("");
+                        index += 2;
+                        while index < file_contents_len
+                            && (char_at(index).is_digit(radix) || char_at(index) == '_')
+                        {
+                            if char_at(index) != '_' {
+                                digits = digits + &char_at(index).to_string();
+                            }
+                            lexeme = lexeme + &char_at(index).to_string();
+                            index += 1;
+                        }
+                        index -= 1;
+
+                        // Stored in decimal so the parser and printers only deal with one form.
+                        match i64::from_str_radix(&digits, radix) {
+                            Ok(n) => {
+                                Self::add_token(self, TokenType::NUMBER, lexeme, Some(n.to_string()))
+                            }
+                            Err(_) => {
+                                Self::lexer_error(
+                                    self,
+                                    format!("Invalid integer literal: {}", lexeme),
+                                    byte_offset(index + 1),
+                                );
+                                result = 65;
+                            }
+                        }
+                    } else if Self::is_digit(char) {
+                        let mut lexeme = String::from("");
                         let mut literal = String::from("");
                         let mut is_float = false;
                         while index < file_contents_len
-                            && (Self::is_digit(char_at(index)) || char_at(index) == '.')
+                            && (Self::is_digit(char_at(index))
+                                || char_at(index) == '.'
+                                || (char_at(index) == '_' && Self::is_digit(char_at(index + 1))))
                         {
                             if char_at(index) == '.' {
                                 if is_float == true {
@@ -198,12 +406,15 @@ impl Tokenizer {
                                 is_float = true;
                             }
 
-                            literal = literal + &char_at(index).to_string();
+                            if char_at(index) != '_' {
+                                literal = literal + &char_at(index).to_string();
+                            }
+                            lexeme = lexeme + &char_at(index).to_string();
                             index += 1;
                         }
                         index -= 1;
 
-                        Self::add_token(self, TokenType::NUMBER, literal.clone(), Some(literal));
+                        Self::add_token(self, TokenType::NUMBER, lexeme, Some(literal));
                     } else if Self::is_alpha(char) {
                         let mut literal = String::from("");
                         while index < file_contents_len && Self::is_alpha_numberic(char_at(index)) {
@@ -220,8 +431,9 @@ impl Tokenizer {
                         );
                     } else {
                         Self::lexer_error(
-                            line,
+                            self,
                             String::from(format!("Unexpected character: {}", char)),
+                            byte_offset(index + 1),
                         );
                         result = 65;
                     }
@@ -238,12 +450,84 @@ impl Tokenizer {
         return self.tokens.clone();
     }
 
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    pub fn get_errors(&self) -> &[LexError] {
+        return &self.errors;
+    }
+
     pub fn print_tokens(&self) {
         for token in self.tokens.clone() {
             token.to_string();
         }
         println!("EOF  null");
     }
+
+    // Tokens and lexer errors as one JSON array, in source order, ending with EOF.
+    pub fn print_tokens_json(&self, file_contents: &str) {
+        let mut entries: Vec<(usize, String)> = Vec::new();
+
+        for token in &self.tokens {
+            let literal = match token.token_type {
+                TokenType::STRING => json_string(&token.literal.clone().unwrap_or_default()),
+                TokenType::NUMBER => {
+                    let literal = token.literal.clone().unwrap_or_default();
+                    match literal.parse::<i64>() {
+                        Ok(n) => n.to_string(),
+                        Err(_) => number_to_json(literal.parse::<f64>().unwrap_or_default()),
+                    }
+                }
+                _ => "null".to_string(),
+            };
+            let (start, end) = token.byte_range();
+            entries.push((
+                start,
+                format!(
+                    "{{\"type\":\"{:?}\",\"lexeme\":{},\"literal\":{},\"line\":{},\"column\":{},\"start\":{},\"end\":{}}}",
+                    token.token_type,
+                    json_string(&token.lexeme),
+                    literal,
+                    token.line,
+                    token.column,
+                    start,
+                    end
+                ),
+            ));
+        }
+
+        for error in &self.errors {
+            entries.push((
+                error.start,
+                format!(
+                    "{{\"type\":\"ERROR\",\"message\":{},\"line\":{},\"column\":{},\"start\":{},\"end\":{}}}",
+                    json_string(&error.message),
+                    error.line,
+                    error.column,
+                    error.start,
+                    error.end
+                ),
+            ));
+        }
+
+        entries.sort_by_key(|entry| entry.0);
+
+        let eof_column = file_contents.lines().last().unwrap_or_default().chars().count() + 1;
+        entries.push((
+            file_contents.len(),
+            format!(
+                "{{\"type\":\"EOF\",\"lexeme\":\"\",\"literal\":null,\"line\":{},\"column\":{},\"start\":{},\"end\":{}}}",
+                self.line,
+                if file_contents.ends_with('\n') { 1 } else { eof_column },
+                file_contents.len(),
+                file_contents.len()
+            ),
+        ));
+
+        let items: Vec<String> = entries.into_iter().map(|entry| entry.1).collect();
+        println!("[{}]", items.join(","));
+    }
 }
 
 